@@ -0,0 +1,84 @@
+use std::backtrace::Backtrace;
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::hash::{ Hash, Hasher };
+use std::sync::Arc;
+use std::thread::ThreadId;
+use std::time::SystemTime;
+
+/// A record of the take that won the value of a `HandOff`.
+///
+/// Only available with the `diagnostics` feature, see
+/// [`HandOff::taken_by`](crate::HandOff::taken_by). The record is shared
+/// by every handle that reports it: two `TakenBy`s are equal if they describe
+/// the same take.
+#[derive(Clone)]
+pub struct TakenBy(Arc<Record>);
+
+struct Record {
+    thread_id: ThreadId,
+    thread_name: Option<String>,
+    time: SystemTime,
+    backtrace: Backtrace,
+}
+
+impl TakenBy {
+    /// Records a take by the current thread.
+    pub(crate) fn capture() -> Self {
+        let thread = std::thread::current();
+        Self(Arc::new(Record {
+            thread_id: thread.id(),
+            thread_name: thread.name().map(String::from),
+            time: SystemTime::now(),
+            backtrace: Backtrace::capture(),
+        }))
+    }
+
+    /// Returns the id of the thread that took the value.
+    pub fn thread_id(&self) -> ThreadId {
+        self.0.thread_id
+    }
+
+    /// Returns the name of the thread that took the value, if it has one.
+    pub fn thread_name(&self) -> Option<&str> {
+        self.0.thread_name.as_deref()
+    }
+
+    /// Returns when the value was taken.
+    pub fn time(&self) -> SystemTime {
+        self.0.time
+    }
+
+    /// Returns the backtrace of the take.
+    ///
+    /// It is captured with [`Backtrace::capture`], so it is only resolved if
+    /// the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables ask
+    /// for it.
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.0.backtrace
+    }
+}
+
+impl Debug for TakenBy {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("TakenBy")
+            .field("thread_id", &self.0.thread_id)
+            .field("thread_name", &self.0.thread_name)
+            .field("time", &self.0.time)
+            .field("backtrace", &self.0.backtrace)
+            .finish()
+    }
+}
+
+impl PartialEq for TakenBy {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TakenBy {}
+
+impl Hash for TakenBy {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
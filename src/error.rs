@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::fmt::{ Debug, Display, Formatter, Result as FmtResult };
+use std::hash::{ Hash, Hasher };
+use std::sync::Arc;
+
+/// The reason a value could not be taken out of a `HandOff`.
+///
+/// A `HandOff` never holds a lock while a taker runs user code, so unlike a
+/// `Mutex` it cannot be poisoned by a panicking thread: the value stays in
+/// place until some handle actually takes it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TakeError {
+    /// The value was already taken by another handle.
+    Taken,
+    /// The `HandOff` was created empty and has not been filled yet.
+    Empty,
+    /// Waiting for the `HandOff` to be filled took longer than the timeout.
+    TimedOut,
+    /// The value is temporarily claimed by a [`Lease`](crate::Lease), a
+    /// [`Reservation`](crate::Reservation) or a closure inspecting it, such as
+    /// [`HandOff::with_ref`](crate::HandOff::with_ref), or is being produced
+    /// by the initializer of a lazy `HandOff`, and may become available
+    /// again.
+    Claimed,
+    /// The `HandOff` holds a value of a different generation than the one
+    /// requested from a reusable `HandOff`.
+    Stale,
+    /// The `HandOff` is lazy and its value is only produced by the handle that
+    /// takes it, so it cannot be inspected or claimed before that.
+    Lazy,
+    /// The initializer of a lazy `HandOff` failed.
+    ///
+    /// Depending on the [`LazyPolicy`](crate::LazyPolicy), either every taker
+    /// sees the same error or only the taker that ran the initializer.
+    InitFailed {
+        /// The error returned by the initializer.
+        error: InitError,
+    },
+    /// Every handle that could take the value was dropped without taking it,
+    /// so a [`Giver`](crate::Giver) waiting for its value to be taken would
+    /// wait forever.
+    Abandoned,
+    /// The `HandOff` was cancelled before its value was taken.
+    Cancelled {
+        /// The reason given to [`HandOff::cancel_with`], if any.
+        ///
+        /// [`HandOff::cancel_with`]: crate::HandOff::cancel_with
+        reason: Option<Arc<str>>,
+    },
+}
+
+impl Display for TakeError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            TakeError::Taken => write!(fmt, "the value was already taken"),
+            TakeError::Empty => write!(fmt, "the handoff was not filled yet"),
+            TakeError::TimedOut => write!(fmt, "timed out waiting for the handoff to be filled"),
+            TakeError::Claimed => write!(fmt, "the value is temporarily claimed"),
+            TakeError::Stale => write!(fmt, "the value belongs to another generation"),
+            TakeError::Lazy => write!(fmt, "the value of the lazy handoff was not produced yet"),
+            TakeError::InitFailed { error } => {
+                write!(fmt, "the initializer of the lazy handoff failed: {error}")
+            },
+            TakeError::Abandoned => write!(fmt, "every taker was dropped without taking the value"),
+            TakeError::Cancelled { reason: None } => write!(fmt, "the handoff was cancelled"),
+            TakeError::Cancelled { reason: Some(reason) } => {
+                write!(fmt, "the handoff was cancelled: {reason}")
+            },
+        }
+    }
+}
+
+impl Error for TakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TakeError::InitFailed { error } => Some(error.get()),
+            _ => None,
+        }
+    }
+}
+
+/// The error returned by the initializer of a
+/// [`HandOff::try_lazy`](crate::HandOff::try_lazy).
+///
+/// It is shared by every taker that observes the failure: two `InitError`s
+/// are equal if they come from the same failure.
+#[derive(Clone)]
+pub struct InitError(Arc<dyn Error + Send + Sync>);
+
+impl InitError {
+    pub(crate) fn new(error: impl Error + Send + Sync + 'static) -> Self {
+        Self(Arc::new(error))
+    }
+
+    /// Returns the error returned by the initializer.
+    pub fn get(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.0
+    }
+
+    /// Returns the error returned by the initializer if it is of type `E`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{ Error, ErrorKind };
+    /// use takeit::{ HandOff, LazyPolicy, TakeError };
+    ///
+    /// let handoff = HandOff::<i32>::try_lazy(LazyPolicy::Poison, || {
+    ///     Err(Error::from(ErrorKind::ConnectionRefused))
+    /// });
+    ///
+    /// let Err(TakeError::InitFailed { error }) = handoff.try_take() else {
+    ///     unreachable!();
+    /// };
+    /// assert_eq!(error.downcast_ref::<Error>().unwrap().kind(), ErrorKind::ConnectionRefused);
+    /// ```
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Debug for InitError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        Debug::fmt(&*self.0, fmt)
+    }
+}
+
+impl Display for InitError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        Display::fmt(&*self.0, fmt)
+    }
+}
+
+impl PartialEq for InitError {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for InitError {}
+
+impl Hash for InitError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
@@ -0,0 +1,147 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ Context, Poll };
+
+use crate::inner::Inner;
+use crate::lazy::{ AsyncInit, Took };
+use crate::{ HandOff, TakeError };
+
+/// A future that resolves to the value of a `HandOff` once it is available.
+///
+/// Created by [`HandOff::take_async`]. It resolves to `None` once the value
+/// was taken by another handle.
+///
+/// The future only moves the value out of the `HandOff` in the poll that
+/// returns it, so dropping it early never loses the value and removes its
+/// waker from the `HandOff`. If it was awaiting the initializer of a
+/// [`HandOff::lazy_async`], the initializer is handed back unfinished for the
+/// next taker to continue.
+#[must_use = "futures do nothing unless polled"]
+pub struct TakeFuture<T> {
+    handoff: HandOff<T>,
+    key: Option<usize>,
+    init: Option<AsyncInit<T>>,
+}
+
+impl<T> TakeFuture<T> {
+    pub(crate) fn new(handoff: HandOff<T>) -> Self {
+        Self { handoff, key: None, init: None }
+    }
+}
+
+impl<T> Future for TakeFuture<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        if this.init.is_none() {
+            match this.handoff.0.poll_take_or_init(&mut this.key, cx) {
+                Poll::Ready(Ok(Took::Value(val))) => return Poll::Ready(Some(val)),
+                Poll::Ready(Ok(Took::Init(init))) => this.init = Some(init),
+                Poll::Ready(Err(_)) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        this.handoff.0.poll_init(&mut this.init, cx).map(Some)
+    }
+}
+
+impl<T> Drop for TakeFuture<T> {
+    fn drop(&mut self) {
+        self.handoff.0.unregister(&mut self.key);
+        if let Some(init) = self.init.take() {
+            self.handoff.0.abort_init(init);
+        }
+    }
+}
+
+/// A future that resolves once the value of a handoff was taken by some
+/// handle.
+///
+/// Created by [`HandOff::wait_taken_async`] and
+/// [`Giver::wait_taken_async`](crate::Giver::wait_taken_async). It resolves to
+/// [`TakeError::Cancelled`] if the handoff is cancelled instead, or to
+/// [`TakeError::Abandoned`] once every handle that could take the value was
+/// dropped. Dropping it early removes its waker from the handoff.
+#[must_use = "futures do nothing unless polled"]
+pub struct TakenFuture<'a, T> {
+    inner: &'a Inner<T>,
+    key: Option<usize>,
+}
+
+impl<'a, T> TakenFuture<'a, T> {
+    pub(crate) fn new(inner: &'a Inner<T>) -> Self {
+        Self { inner, key: None }
+    }
+}
+
+impl<T> Future for TakenFuture<'_, T> {
+    type Output = Result<(), TakeError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), TakeError>> {
+        let this = self.get_mut();
+        this.inner.poll_taken(&mut this.key, cx)
+    }
+}
+
+impl<T> Drop for TakenFuture<'_, T> {
+    fn drop(&mut self) {
+        self.inner.unregister(&mut self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+    use std::thread;
+
+    use crate::waiters::block_on_future;
+
+    #[test]
+    fn test_take_async_threads() {
+        let handoff = HandOff::empty();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                thread::spawn(move || block_on_future(handoff_clone.take_async()))
+            })
+            .collect();
+
+        thread::sleep(std::time::Duration::from_millis(20));
+        handoff.fill(String::from("job")).unwrap();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners, vec![String::from("job")]);
+    }
+
+    #[test]
+    fn test_wait_taken_async() {
+        let (giver, taker) = HandOff::pair(5);
+
+        let waiter = thread::spawn(move || block_on_future(giver.wait_taken_async()));
+        thread::sleep(std::time::Duration::from_millis(20));
+
+        assert_eq!(taker.take(), Some(5));
+        assert_eq!(waiter.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn test_drop_pending_future() {
+        let handoff = HandOff::empty();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut future = Box::pin(handoff.clone().take_async());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        drop(future);
+
+        handoff.fill(3).unwrap();
+        assert_eq!(block_on_future(handoff.take_async()), Some(3));
+    }
+}
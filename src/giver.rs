@@ -0,0 +1,215 @@
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::panic::{ RefUnwindSafe, UnwindSafe };
+use std::sync::Arc;
+use std::time::{ Duration, Instant };
+
+use crate::inner::Inner;
+use crate::{ HandOff, HandOffState, TakeError, TakenFuture, Taker };
+#[cfg(feature = "diagnostics")]
+use crate::TakenBy;
+
+/// The originating side of a `HandOff`.
+///
+/// A `Giver` hands out [`Taker`] handles to consumers, but it cannot take the
+/// value itself. Instead it is the only handle that can fill an empty
+/// handoff, cancel it, or withdraw the value with [`Giver::reclaim`], e.g.
+/// when shutting down before any consumer showed up. It cannot be cloned, so
+/// there is exactly one originator per value.
+///
+/// Dropping a `Giver` that never filled its handoff cancels the handoff, so
+/// takers waiting for a value fail with
+/// [`TakeError::Cancelled`](crate::TakeError::Cancelled) instead of waiting
+/// forever.
+///
+/// # Example
+/// ```
+/// use takeit::Giver;
+///
+/// let giver = Giver::new(String::from("job"));
+/// let taker = giver.taker();
+///
+/// // Nobody took the job, so the originator gets it back.
+/// assert_eq!(giver.reclaim().ok(), Some(String::from("job")));
+/// assert_eq!(taker.take(), None);
+/// ```
+pub struct Giver<T>(Arc<Inner<T>>);
+
+impl<T> Giver<T> {
+    /// Creates a new Giver object initialized with a value of type `T`
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::new(10);
+    /// ```
+    pub fn new(val: T) -> Self {
+        Self(Arc::new(Inner::new(val)))
+    }
+
+    /// Creates a new Giver object without a value, to be provided later with
+    /// [`Giver::fill`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::<i32>::empty();
+    /// ```
+    pub fn empty() -> Self {
+        Self(Arc::new(Inner::empty()))
+    }
+
+    /// Returns a new `Taker` handle sharing the value of this `Giver`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::new(10);
+    /// let taker = giver.taker();
+    ///
+    /// assert_eq!(taker.take(), Some(10));
+    /// ```
+    pub fn taker(&self) -> Taker<T> {
+        Taker(HandOff::from_inner(self.0.clone()))
+    }
+
+    /// Fills an empty `Giver` with a value, making it available to every
+    /// taker. See [`HandOff::fill`].
+    ///
+    /// # Errors
+    /// If the `Giver` already holds a value, its value was already taken, or
+    /// it was cancelled, `val` is handed back in `Err`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::empty();
+    /// let taker = giver.taker();
+    ///
+    /// assert_eq!(giver.fill(1), Ok(()));
+    /// assert_eq!(taker.take(), Some(1));
+    /// ```
+    pub fn fill(&self, val: T) -> Result<(), T> {
+        self.0.fill(val).map(|_| ())
+    }
+
+    /// Cancels the handoff, returning its value if nobody took it yet. See
+    /// [`HandOff::cancel`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::new(10);
+    /// let taker = giver.taker();
+    ///
+    /// assert_eq!(giver.cancel(), Some(10));
+    /// assert_eq!(taker.take(), None);
+    /// ```
+    pub fn cancel(&self) -> Option<T> {
+        self.0.cancel(None)
+    }
+
+    /// Cancels the handoff like [`Giver::cancel`], recording a reason that
+    /// takers receive in [`TakeError::Cancelled`](crate::TakeError::Cancelled).
+    pub fn cancel_with(&self, reason: impl Into<Arc<str>>) -> Option<T> {
+        self.0.cancel(Some(reason.into()))
+    }
+
+    /// Withdraws the value from every handle if none of them took it yet.
+    ///
+    /// After a successful reclaim the handoff is cancelled, so takers fail
+    /// with [`TakeError::Cancelled`](crate::TakeError::Cancelled).
+    ///
+    /// # Errors
+    /// If the value was already taken, was never filled, or the handoff was
+    /// cancelled, the `Giver` is handed back in `Err`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::Giver;
+    ///
+    /// let giver = Giver::new(10);
+    /// giver.taker().take();
+    ///
+    /// assert!(giver.reclaim().is_err());
+    /// ```
+    pub fn reclaim(self) -> Result<T, Self> {
+        self.0.withdraw().ok_or(self)
+    }
+
+    /// Blocks the current thread until some taker took the value. See
+    /// [`HandOff::wait_taken`].
+    ///
+    /// # Errors
+    /// Returns [`TakeError::Cancelled`] if the handoff is cancelled instead,
+    /// or [`TakeError::Abandoned`] once every [`Taker`] was dropped without
+    /// taking the value.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let (giver, taker) = HandOff::pair(10);
+    /// let consumer = std::thread::spawn(move || taker.take());
+    ///
+    /// assert_eq!(giver.wait_taken(), Ok(()));
+    /// assert_eq!(consumer.join().unwrap(), Some(10));
+    ///
+    /// let (giver, taker) = HandOff::pair(10);
+    /// drop(taker);
+    /// assert_eq!(giver.wait_taken(), Err(TakeError::Abandoned));
+    /// ```
+    pub fn wait_taken(&self) -> Result<(), TakeError> {
+        self.0.wait_taken(None)
+    }
+
+    /// Blocks the current thread for at most `timeout` until some taker took
+    /// the value. See [`HandOff::wait_taken_timeout`].
+    ///
+    /// # Errors
+    /// Returns [`TakeError::TimedOut`] if nobody took the value in time,
+    /// [`TakeError::Cancelled`] if the handoff is cancelled instead, or
+    /// [`TakeError::Abandoned`] once every [`Taker`] was dropped without
+    /// taking the value.
+    pub fn wait_taken_timeout(&self, timeout: Duration) -> Result<(), TakeError> {
+        self.0.wait_taken(Some(Instant::now() + timeout))
+    }
+
+    /// Returns a future that resolves once some taker took the value. See
+    /// [`HandOff::wait_taken_async`].
+    pub fn wait_taken_async(&self) -> TakenFuture<'_, T> {
+        TakenFuture::new(&self.0)
+    }
+
+    /// Returns the current state of the handoff. See [`HandOff::state`].
+    pub fn state(&self) -> HandOffState {
+        self.0.state()
+    }
+
+    /// Returns the record of the take that won the value. See
+    /// [`HandOff::taken_by`].
+    #[cfg(feature = "diagnostics")]
+    pub fn taken_by(&self) -> Option<TakenBy> {
+        self.0.taken_by()
+    }
+}
+
+impl<T> Drop for Giver<T> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+impl<T: Debug> Debug for Giver<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        self.0.fmt_debug("Giver", fmt)
+    }
+}
+
+// See the matching impls of `HandOff`.
+impl<T> UnwindSafe for Giver<T> {}
+impl<T> RefUnwindSafe for Giver<T> {}
@@ -0,0 +1,928 @@
+use std::cell::UnsafeCell;
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::mem::MaybeUninit;
+use std::ops::{ Deref, DerefMut };
+use std::sync::atomic::{ AtomicU64, AtomicU8, AtomicUsize, Ordering };
+#[cfg(feature = "diagnostics")]
+use std::sync::{ Mutex, PoisonError };
+use std::sync::{ Arc, OnceLock };
+use std::task::{ Context, Poll, Waker };
+use std::time::Instant;
+
+use crate::{ HandOffState, InitError, LazyPolicy, TakeError };
+#[cfg(feature = "diagnostics")]
+use crate::TakenBy;
+use crate::lazy::{ AsyncInit, Init, InitMode, Took };
+use crate::waiters::{ self, Waiters };
+
+/// The value is stored in the slot and nobody has claimed it yet.
+const UNTAKEN: u8 = 0;
+/// A taker won the race and is moving the value out of the slot.
+const TAKING: u8 = 2;
+/// The value was moved out of the slot.
+const TAKEN: u8 = 3;
+/// The slot was never filled with a value.
+const EMPTY: u8 = 4;
+/// A producer is moving a value into the empty slot.
+const FILLING: u8 = 5;
+/// A handle is cancelling the handoff and recording the reason. Takers wait
+/// for the cancellation to be published.
+const CANCELLING: u8 = 6;
+/// The handoff was cancelled and the value, if any, was moved out.
+const CANCELLED: u8 = 7;
+/// A lease or a `Locked` guard has exclusive access to the value and will
+/// either put it back or take it. Takers treat the value as unavailable
+/// rather than waiting.
+const CLAIMED: u8 = 8;
+/// The handoff was cancelled while the value was claimed. The value is
+/// dropped when the claim is released, unless the claim takes it.
+const CANCEL_PENDING: u8 = 9;
+/// The value was not produced yet: the taker that wins the slot runs `init`
+/// to produce it.
+const LAZY: u8 = 10;
+/// A taker is running `init`. Other takers treat the value as unavailable
+/// rather than waiting, since `init` may fail and leave the slot `LAZY`.
+const INITIALIZING: u8 = 11;
+/// `init` failed and the error in `poison` is reported to every taker.
+const POISONED: u8 = 12;
+/// The handoff was cancelled while a taker was running `init`. The slot is
+/// cancelled when `init` fails or is handed back, unless it produces the
+/// value, which is then taken.
+const INIT_CANCEL_PENDING: u8 = 13;
+
+/// The state shared between all the clones of a `HandOff`.
+///
+/// The value lives in `value` and is only initialized while `state` is
+/// `UNTAKEN`, `CLAIMED` or `CANCEL_PENDING`. Whoever moves `state`
+/// away from `UNTAKEN` with a compare-and-swap gets exclusive access to the
+/// slot until it publishes the next state. Likewise, whoever moves `state`
+/// away from `LAZY` gets exclusive access to `init`.
+pub(crate) struct Inner<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// Counts the live `HandOff` handles, which does not include the internal
+    /// references held by leases, givers and mapped handoffs.
+    handles: AtomicUsize,
+    /// Shared with the source of a mapped slot, so that waiting on either
+    /// wakes up on changes of both.
+    waiters: Arc<Waiters>,
+    on_unclaimed: Option<OnUnclaimed<T>>,
+    cancel_reason: OnceLock<Arc<str>>,
+    /// Counts the values moved into the slot. Only written while filling.
+    generation: AtomicU64,
+    /// Whether the slot can be filled again after its value was taken.
+    reusable: bool,
+    init: UnsafeCell<Option<Init<T>>>,
+    /// Whether `init` is async, so that takers which cannot wait for it leave
+    /// the slot alone instead of winning it.
+    async_init: bool,
+    /// Set before the state is published as `POISONED`.
+    poison: OnceLock<InitError>,
+    /// The handoff a mapped slot takes its value from. Its state stands in for
+    /// the state of the slot while the slot is `LAZY`.
+    source: Option<Arc<dyn Source>>,
+    /// Written before the state is published as `TAKEN`.
+    #[cfg(feature = "diagnostics")]
+    taken_by: Mutex<Option<TakenBy>>,
+}
+
+/// A handoff that a mapped handoff takes its value from.
+pub(crate) trait Source: Send + Sync {
+    fn state(&self) -> HandOffState;
+    fn taken(&self) -> Option<Result<(), TakeError>>;
+}
+
+/// Receives the value if the last handle is dropped before anyone took it.
+pub(crate) type OnUnclaimed<T> = Box<dyn FnOnce(T) + Send>;
+
+// SAFETY: The value is only ever accessed by the single thread that won the
+// transition out of `UNTAKEN`, so sharing the `Inner` is like sharing a
+// `Mutex<T>`. The same goes for `init` and the transition out of `LAZY`.
+// `on_unclaimed` is only accessed through `&mut self` on drop.
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    fn with_slot(state: u8, value: MaybeUninit<T>, generation: u64) -> Self {
+        Self {
+            state: AtomicU8::new(state),
+            value: UnsafeCell::new(value),
+            handles: AtomicUsize::new(0),
+            waiters: Arc::new(Waiters::new()),
+            on_unclaimed: None,
+            cancel_reason: OnceLock::new(),
+            generation: AtomicU64::new(generation),
+            reusable: false,
+            init: UnsafeCell::new(None),
+            async_init: false,
+            poison: OnceLock::new(),
+            source: None,
+            #[cfg(feature = "diagnostics")]
+            taken_by: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn lazy(init: Init<T>) -> Self {
+        let mut inner = Self::with_slot(LAZY, MaybeUninit::uninit(), 1);
+        inner.async_init = matches!(init, Init::Async(_));
+        inner.init = UnsafeCell::new(Some(init));
+        inner
+    }
+
+    /// Creates a lazy slot whose `init` takes the value from `source`.
+    pub(crate) fn mapped<S: Send + 'static>(init: Init<T>, source: Arc<Inner<S>>) -> Self {
+        let mut inner = Self::lazy(init);
+        inner.waiters = source.waiters.clone();
+        inner.source = Some(source);
+        inner
+    }
+
+    pub(crate) fn new(val: T) -> Self {
+        Self::with_slot(UNTAKEN, MaybeUninit::new(val), 1)
+    }
+
+    pub(crate) fn empty() -> Self {
+        Self::with_slot(EMPTY, MaybeUninit::uninit(), 0)
+    }
+
+    pub(crate) fn reusable() -> Self {
+        let mut inner = Self::empty();
+        inner.reusable = true;
+        inner
+    }
+
+    pub(crate) fn with_on_unclaimed(val: T, on_unclaimed: OnUnclaimed<T>) -> Self {
+        let mut inner = Self::new(val);
+        inner.on_unclaimed = Some(on_unclaimed);
+        inner
+    }
+
+    /// Moves the value out of a slot that no other handle can reach anymore.
+    ///
+    /// The initializer of a lazy slot is never run, since a mapped slot would
+    /// take the value from a source that other handles can still reach.
+    pub(crate) fn into_value(mut self) -> Option<T> {
+        let state = self.state.get_mut();
+        if *state != UNTAKEN {
+            return None;
+        }
+
+        *state = TAKEN;
+        // SAFETY: The value was never moved out of the slot, and it will not
+        // be dropped again now that the state is `TAKEN`.
+        Some(unsafe { self.value.get_mut().assume_init_read() })
+    }
+
+    pub(crate) fn state(&self) -> HandOffState {
+        match self.state.load(Ordering::Acquire) {
+            LAZY => self.source.as_ref().map_or(HandOffState::Available, |source| source.state()),
+            UNTAKEN => HandOffState::Available,
+            TAKING | TAKEN => HandOffState::Taken,
+            CANCELLING | CANCEL_PENDING | INIT_CANCEL_PENDING | CANCELLED => {
+                HandOffState::Cancelled
+            },
+            CLAIMED | INITIALIZING => HandOffState::Claimed,
+            POISONED => HandOffState::Poisoned,
+            _ => HandOffState::Empty,
+        }
+    }
+
+    /// Moves a value into the slot if it was never filled, or if its previous
+    /// value was taken and the slot is reusable. Hands the value back
+    /// otherwise.
+    ///
+    /// Returns the generation of the new value.
+    pub(crate) fn fill(&self, val: T) -> Result<u64, T> {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            match current {
+                EMPTY | TAKEN if current == EMPTY || self.reusable => {
+                    match self.state.compare_exchange_weak(
+                        current,
+                        FILLING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => break,
+                        Err(actual) => current = actual,
+                    }
+                },
+                TAKING if self.reusable => {
+                    std::thread::yield_now();
+                    current = self.state.load(Ordering::Acquire);
+                },
+                _ => return Err(val),
+            }
+        }
+
+        // SAFETY: We won the transition out of `EMPTY` or `TAKEN`, so the slot
+        // is uninitialized and nobody else can access it.
+        unsafe { (*self.value.get()).write(val) };
+        let generation = self.generation.load(Ordering::Relaxed) + 1;
+        self.generation.store(generation, Ordering::Relaxed);
+        self.state.store(UNTAKEN, Ordering::Release);
+        self.waiters.notify();
+        Ok(generation)
+    }
+
+    /// Returns the generation of the value in the slot, or of the last value
+    /// if it was taken. Zero if the slot was never filled.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Moves the value out of the slot only if it belongs to `generation`.
+    pub(crate) fn take_generation(&self, generation: u64) -> Result<T, TakeError> {
+        let locked_value = self.lock()?;
+        if self.generation.load(Ordering::Relaxed) != generation {
+            return Err(TakeError::Stale);
+        }
+
+        Ok(locked_value.take())
+    }
+
+    /// Moves the state from `UNTAKEN` to `to`, granting exclusive access to
+    /// the value.
+    ///
+    /// Losing the race never blocks: if another handle already moved the
+    /// state on, this fails right away. It only waits while the handoff is
+    /// being cancelled.
+    fn acquire(&self, to: u8) -> Result<(), TakeError> {
+        loop {
+            match self.state.compare_exchange_weak(
+                UNTAKEN,
+                to,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(CANCELLING) => std::thread::yield_now(),
+                Err(UNTAKEN) => {},
+                Err(EMPTY | FILLING) => return Err(TakeError::Empty),
+                Err(CLAIMED | INITIALIZING) => return Err(TakeError::Claimed),
+                Err(POISONED) => return Err(self.poisoned()),
+                Err(CANCEL_PENDING | INIT_CANCEL_PENDING | CANCELLED) => {
+                    return Err(self.cancelled());
+                },
+                Err(LAZY) => return Err(self.lazy_error()),
+                Err(_) => return Err(TakeError::Taken),
+            }
+        }
+    }
+
+    /// Publishes the next state of a slot we have exclusive access to, and
+    /// wakes the waiters.
+    ///
+    /// With the `diagnostics` feature, a `TAKEN` state is recorded as taken
+    /// by the current thread first.
+    fn publish(&self, state: u8) {
+        #[cfg(feature = "diagnostics")]
+        if state == TAKEN {
+            *self.taken_by.lock().unwrap_or_else(PoisonError::into_inner) =
+                Some(TakenBy::capture());
+        }
+
+        self.state.store(state, Ordering::Release);
+        self.waiters.notify();
+    }
+
+    /// Publishes the outcome of `init` for the taker that won the transition
+    /// out of `LAZY`.
+    ///
+    /// If the handoff was cancelled in the meantime, an outcome other than
+    /// `TAKEN` cancels the slot instead, dropping `init` if it was put back.
+    fn finish_init(&self, state: u8) {
+        if state == TAKEN {
+            self.publish(TAKEN);
+            return;
+        }
+
+        match self.state.compare_exchange(
+            INITIALIZING,
+            state,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => self.waiters.notify(),
+            Err(_) => {
+                // SAFETY: Only the winner leaves `INIT_CANCEL_PENDING`, so we
+                // still own `init`.
+                drop(unsafe { (*self.init.get()).take() });
+                self.publish(CANCELLED);
+            },
+        }
+    }
+
+    /// Returns the record of the take that won the value, if the value was
+    /// taken.
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn taken_by(&self) -> Option<TakenBy> {
+        if self.state.load(Ordering::Acquire) != TAKEN {
+            return None;
+        }
+
+        self.taken_by.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// The error reported to handles that need the value of a `LAZY` slot
+    /// before it was produced.
+    ///
+    /// A mapped slot reports why its source can no longer be taken instead.
+    fn lazy_error(&self) -> TakeError {
+        let Some(source) = &self.source else {
+            return TakeError::Lazy;
+        };
+
+        match source.taken() {
+            Some(Ok(())) => TakeError::Taken,
+            Some(Err(err)) => err,
+            None => TakeError::Lazy,
+        }
+    }
+
+    /// Moves the value out of the slot if nobody took it yet.
+    ///
+    /// Never waits: a slot with an async initializer fails with
+    /// `TakeError::Lazy`.
+    pub(crate) fn take(&self) -> Result<T, TakeError> {
+        self.take_with(InitMode::Try)
+    }
+
+    /// Like `take`, but runs an async initializer to completion on the
+    /// current thread unless `mode` is `InitMode::Try`.
+    pub(crate) fn take_with(&self, mode: InitMode) -> Result<T, TakeError> {
+        self.take_or_init(mode).map(|took| self.finish_blocking(took))
+    }
+
+    /// Returns the value a taker got, running its async initializer to
+    /// completion on the current thread.
+    pub(crate) fn finish_blocking(&self, took: Took<T>) -> T {
+        match took {
+            Took::Value(val) => val,
+            Took::Init(init) => {
+                // Publishes the value as taken even if `init` panics.
+                let _taken = Publish::new(self, TAKEN);
+                waiters::block_on_future(init)
+            },
+        }
+    }
+
+    /// Moves the value out of the slot, producing it first if the slot is
+    /// lazy. An async initializer is handed to the caller instead of being
+    /// run, or left alone in `InitMode::Try`.
+    ///
+    /// Only the taker that wins a lazy slot runs its initializer. The losers
+    /// fail with `TakeError::Claimed` until the winner publishes the outcome.
+    pub(crate) fn take_or_init(&self, mode: InitMode) -> Result<Took<T>, TakeError> {
+        loop {
+            match self.acquire(TAKING) {
+                Ok(()) => {
+                    // SAFETY: We won the transition out of `UNTAKEN`, so the
+                    // value is initialized and nobody else can access it.
+                    let val = unsafe { (*self.value.get()).assume_init_read() };
+                    self.publish(TAKEN);
+                    return Ok(Took::Value(val));
+                },
+                Err(TakeError::Lazy) if mode == InitMode::Try && self.async_init => {
+                    return Err(TakeError::Lazy);
+                },
+                Err(TakeError::Lazy) => {},
+                Err(err) => return Err(err),
+            }
+
+            // A mapped slot waits for its source without winning the slot, as
+            // handing the slot back would wake the waiters it shares with the
+            // source, including the caller.
+            match self.source.as_ref().map(|source| source.state()) {
+                Some(HandOffState::Empty) => return Err(TakeError::Empty),
+                Some(HandOffState::Claimed) => return Err(TakeError::Claimed),
+                _ => {},
+            }
+
+            if self.state.compare_exchange(
+                LAZY,
+                INITIALIZING,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ).is_ok() {
+                break;
+            }
+        }
+
+        // SAFETY: We won the transition out of `LAZY`, so nobody else can
+        // access `init`.
+        let init = unsafe { (*self.init.get()).take() };
+
+        // Publishes the value as taken even if `init` panics, so waiters are
+        // not left behind.
+        let mut publish = Publish::new(self, TAKEN);
+        match init.expect("lazy handoff without an initializer") {
+            Init::Once(init) => Ok(Took::Value(init())),
+            Init::Fallible { mut init, policy } => match init() {
+                Ok(val) => Ok(Took::Value(val)),
+                Err(error) => {
+                    match policy {
+                        LazyPolicy::Poison => {
+                            let _ = self.poison.set(error.clone());
+                            publish.state = POISONED;
+                        },
+                        LazyPolicy::Retry => {
+                            // SAFETY: Nobody else leaves `INITIALIZING` or
+                            // `INIT_CANCEL_PENDING` until `publish` is
+                            // dropped, so we still own `init`.
+                            unsafe { *self.init.get() = Some(Init::Fallible { init, policy }) };
+                            publish.state = LAZY;
+                        },
+                    }
+                    Err(TakeError::InitFailed { error })
+                },
+            },
+            // The source is only blocked on by blocking takers, like this slot.
+            Init::Mapped(mut init) => match init(match mode {
+                InitMode::Block => InitMode::Block,
+                InitMode::Try | InitMode::Async => InitMode::Try,
+            }) {
+                Ok(val) => Ok(Took::Value(val)),
+                Err(err) => {
+                    // The source is still the one to decide who gets the value,
+                    // so the slot stays lazy whatever the outcome.
+                    // SAFETY: Nobody else leaves `INITIALIZING` or
+                    // `INIT_CANCEL_PENDING` until `publish` is dropped, so we
+                    // still own `init`.
+                    unsafe { *self.init.get() = Some(Init::Mapped(init)) };
+                    publish.state = LAZY;
+                    Err(err)
+                },
+            },
+            Init::Async(init) => {
+                // The caller owns the slot until it finishes or hands back
+                // the initializer.
+                std::mem::forget(publish);
+                Ok(Took::Init(init))
+            },
+        }
+    }
+
+    /// Polls an async initializer handed out by `poll_take_or_init`, publishing
+    /// the value as taken once it is ready.
+    ///
+    /// `init` is left in place while the initializer is pending, and emptied
+    /// once it completes or panics.
+    pub(crate) fn poll_init(
+        &self,
+        init: &mut Option<AsyncInit<T>>,
+        cx: &mut Context<'_>,
+    ) -> Poll<T> {
+        let mut future = init.take().expect("polled a finished initializer");
+        let taken = Publish::new(self, TAKEN);
+
+        match future.as_mut().poll(cx) {
+            Poll::Ready(val) => Poll::Ready(val),
+            Poll::Pending => {
+                std::mem::forget(taken);
+                *init = Some(future);
+                Poll::Pending
+            },
+        }
+    }
+
+    /// Hands back an unfinished async initializer, so the next taker
+    /// continues it.
+    pub(crate) fn abort_init(&self, init: AsyncInit<T>) {
+        // SAFETY: Nobody else leaves `INITIALIZING` or `INIT_CANCEL_PENDING`
+        // while an async initializer is handed out, so we still own `init`.
+        unsafe { *self.init.get() = Some(Init::Async(init)) };
+        self.finish_init(LAZY);
+    }
+
+    /// Claims exclusive access to the value while leaving it in the slot.
+    ///
+    /// The claim must be ended with either `release_claim` or `take_claimed`.
+    pub(crate) fn claim(&self) -> Result<(), TakeError> {
+        self.acquire(CLAIMED)
+    }
+
+    /// Like `claim`, but blocks while the slot is empty or claimed by someone
+    /// else.
+    pub(crate) fn claim_blocking(&self, deadline: Option<Instant>) -> Result<(), TakeError> {
+        self.waiters
+            .block_on(deadline, || match self.claim() {
+                Err(err) if self.should_wait(&err) => None,
+                result => Some(result),
+            })
+            .unwrap_or(Err(TakeError::TimedOut))
+    }
+
+    /// Returns a pointer to the value. It may only be dereferenced by the
+    /// holder of a claim.
+    pub(crate) fn slot(&self) -> *mut T {
+        self.value.get().cast()
+    }
+
+    /// Ends a claim by putting the value back, or dropping it if the handoff
+    /// was cancelled in the meantime.
+    pub(crate) fn release_claim(&self) {
+        if self.state.compare_exchange(
+            CLAIMED,
+            UNTAKEN,
+            Ordering::Release,
+            Ordering::Acquire,
+        ).is_err() {
+            // SAFETY: The state is `CANCEL_PENDING`, which only the claim
+            // holder can leave, so the value is still initialized.
+            unsafe { (*self.value.get()).assume_init_drop() };
+            self.state.store(CANCELLED, Ordering::Release);
+        }
+
+        self.waiters.notify();
+    }
+
+    /// Ends a claim by moving the value out of the slot.
+    pub(crate) fn take_claimed(&self) -> T {
+        // SAFETY: The claim holder has exclusive access to the value until it
+        // publishes the next state.
+        let val = unsafe { (*self.value.get()).assume_init_read() };
+        self.publish(TAKEN);
+        val
+    }
+
+    /// Cancels the handoff, moving the value out if it was still in the slot.
+    ///
+    /// Does nothing if the value was already taken or the handoff was already
+    /// cancelled. Wakes every waiter so they can observe the cancellation.
+    pub(crate) fn cancel(&self, reason: Option<Arc<str>>) -> Option<T> {
+        self.cancel_slot(reason, true)
+    }
+
+    /// Cancels the handoff only if the value is still in the slot, moving it
+    /// out.
+    pub(crate) fn withdraw(&self) -> Option<T> {
+        self.cancel_slot(None, false)
+    }
+
+    /// Cancels the handoff only if it was never filled, e.g. because the only
+    /// producer went away.
+    pub(crate) fn close(&self) {
+        if self.state.compare_exchange(
+            EMPTY,
+            CANCELLED,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        ).is_ok() {
+            self.waiters.notify();
+        }
+    }
+
+    fn cancel_slot(&self, reason: Option<Arc<str>>, cancel_empty: bool) -> Option<T> {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            match current {
+                // A reusable slot whose value was taken waits for the next
+                // value, so it is cancelled like an empty slot.
+                UNTAKEN | EMPTY | LAZY | TAKEN
+                    if current == UNTAKEN || cancel_empty && (current != TAKEN || self.reusable) =>
+                {
+                    match self.state.compare_exchange_weak(
+                        current,
+                        CANCELLING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => break,
+                        Err(actual) => current = actual,
+                    }
+                },
+                INITIALIZING if cancel_empty => {
+                    // The taker running `init` owns the slot for now, so the
+                    // cancellation completes when it publishes the outcome.
+                    if let Some(reason) = reason.clone() {
+                        let _ = self.cancel_reason.set(reason);
+                    }
+                    match self.state.compare_exchange_weak(
+                        INITIALIZING,
+                        INIT_CANCEL_PENDING,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => {
+                            self.waiters.notify();
+                            return None;
+                        },
+                        Err(actual) => current = actual,
+                    }
+                },
+                CLAIMED if cancel_empty => {
+                    // The claim holder owns the value for now, so the
+                    // cancellation completes when the claim ends.
+                    if let Some(reason) = reason.clone() {
+                        let _ = self.cancel_reason.set(reason);
+                    }
+                    match self.state.compare_exchange_weak(
+                        CLAIMED,
+                        CANCEL_PENDING,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => {
+                            self.waiters.notify();
+                            return None;
+                        },
+                        Err(actual) => current = actual,
+                    }
+                },
+                FILLING | CANCELLING => {
+                    std::thread::yield_now();
+                    current = self.state.load(Ordering::Acquire);
+                },
+                TAKING if self.reusable => {
+                    std::thread::yield_now();
+                    current = self.state.load(Ordering::Acquire);
+                },
+                _ => return None,
+            }
+        }
+
+        let val = (current == UNTAKEN).then(|| {
+            // SAFETY: We won the transition out of `UNTAKEN`, so the value is
+            // initialized and nobody else can access it.
+            unsafe { (*self.value.get()).assume_init_read() }
+        });
+        if current == LAZY {
+            // SAFETY: We won the transition out of `LAZY`, so nobody else can
+            // access `init`.
+            drop(unsafe { (*self.init.get()).take() });
+        }
+
+        if let Some(reason) = reason {
+            let _ = self.cancel_reason.set(reason);
+        }
+        self.state.store(CANCELLED, Ordering::Release);
+        self.waiters.notify();
+        val
+    }
+
+    /// The error reported to takers of a poisoned handoff.
+    fn poisoned(&self) -> TakeError {
+        TakeError::InitFailed {
+            error: self.poison.get().cloned().expect("poisoned handoff without an error"),
+        }
+    }
+
+    /// The error reported to takers of a cancelled handoff.
+    fn cancelled(&self) -> TakeError {
+        TakeError::Cancelled {
+            reason: self.cancel_reason.get().cloned(),
+        }
+    }
+
+    /// Whether a taker that failed with `err` may still get a value by
+    /// waiting for the state to change.
+    ///
+    /// That is the case while the slot is empty or claimed, and on a reusable
+    /// slot also after its value was taken, since it may be filled again.
+    pub(crate) fn should_wait(&self, err: &TakeError) -> bool {
+        match err {
+            TakeError::Empty | TakeError::Claimed => true,
+            TakeError::Taken => self.reusable,
+            _ => false,
+        }
+    }
+
+    /// Like `take`, but blocks while the slot is empty or claimed, or while a
+    /// reusable slot waits to be filled again. An async initializer is run to
+    /// completion on the current thread.
+    ///
+    /// Every blocked taker is woken when the slot is filled or the claim is
+    /// released, and exactly one of them gets the value. Fails with
+    /// `TakeError::TimedOut` if `deadline` passes before the slot is filled.
+    pub(crate) fn take_blocking(&self, deadline: Option<Instant>) -> Result<T, TakeError> {
+        self.waiters
+            .block_on(deadline, || match self.take_with(InitMode::Block) {
+                Err(err) if self.should_wait(&err) => None,
+                result => Some(result),
+            })
+            .unwrap_or(Err(TakeError::TimedOut))
+    }
+
+    /// Like `take_blocking`, but registers the task of `cx` to be woken instead
+    /// of blocking. `key` tracks the registration across polls.
+    ///
+    /// An async initializer is handed to the caller, which must either finish
+    /// it with `poll_init` or hand it back with `abort_init`.
+    pub(crate) fn poll_take_or_init(
+        &self,
+        key: &mut Option<usize>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Took<T>, TakeError>> {
+        self.waiters.poll(key, cx, || match self.take_or_init(InitMode::Async) {
+            Err(err) if self.should_wait(&err) => None,
+            result => Some(result),
+        })
+    }
+
+    /// Like `poll_take_or_init`, but fails with `TakeError::Lazy` instead of
+    /// taking an async initializer.
+    pub(crate) fn poll_take(
+        &self,
+        key: &mut Option<usize>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, TakeError>> {
+        self.waiters.poll(key, cx, || match self.take() {
+            Err(err) if self.should_wait(&err) => None,
+            result => Some(result),
+        })
+    }
+
+    /// Returns whether the value was taken, or `None` while it still may be.
+    fn taken(&self) -> Option<Result<(), TakeError>> {
+        match self.state.load(Ordering::Acquire) {
+            TAKING | TAKEN => Some(Ok(())),
+            CANCELLED => Some(Err(self.cancelled())),
+            POISONED => Some(Err(self.poisoned())),
+            LAZY => self.source.as_ref().and_then(|source| source.taken()),
+            _ => None,
+        }
+    }
+
+    /// Like `taken`, but also fails once no handle is left that could take the
+    /// value. A claim that is still held may take it, so it keeps the value
+    /// alive.
+    fn taken_or_abandoned(&self) -> Option<Result<(), TakeError>> {
+        self.taken().or_else(|| {
+            let abandoned = self.handle_count() == 0
+                && !matches!(self.state.load(Ordering::Acquire), CLAIMED | CANCEL_PENDING);
+            abandoned.then_some(Err(TakeError::Abandoned))
+        })
+    }
+
+    /// Blocks until some handle takes the value.
+    ///
+    /// Fails with `TakeError::Cancelled` if the handoff is cancelled instead,
+    /// with `TakeError::Abandoned` if every handle is dropped without taking
+    /// the value, or with `TakeError::TimedOut` if `deadline` passes first.
+    pub(crate) fn wait_taken(&self, deadline: Option<Instant>) -> Result<(), TakeError> {
+        self.waiters
+            .block_on(deadline, || self.taken_or_abandoned())
+            .unwrap_or(Err(TakeError::TimedOut))
+    }
+
+    /// Like `wait_taken`, but registers the task of `cx` to be woken instead
+    /// of blocking.
+    pub(crate) fn poll_taken(
+        &self,
+        key: &mut Option<usize>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), TakeError>> {
+        self.waiters.poll(key, cx, || self.taken_or_abandoned())
+    }
+
+    pub(crate) fn acquire_handle(&self) {
+        self.handles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Wakes the waiters when the last handle goes away, since nobody can
+    /// take the value anymore.
+    pub(crate) fn release_handle(&self) {
+        if self.handles.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.waiters.notify();
+        }
+    }
+
+    pub(crate) fn handle_count(&self) -> usize {
+        self.handles.load(Ordering::Acquire)
+    }
+
+    /// Registers `waker` to be woken on the next change of the state.
+    pub(crate) fn register(&self, key: &mut Option<usize>, waker: &Waker) {
+        self.waiters.register(key, waker);
+    }
+
+    /// Removes a waker registered by `register`, `poll_take` or `poll_taken`.
+    pub(crate) fn unregister(&self, key: &mut Option<usize>) {
+        self.waiters.unregister(key);
+    }
+
+    /// Claims the value in place for the duration of the returned guard,
+    /// failing if it was already taken, was never filled, or is claimed.
+    ///
+    /// Like a lease, the guard lets other takers fail or wait on the waiter
+    /// list instead of spinning while it is held.
+    pub(crate) fn lock(&self) -> Result<Locked<'_, T>, TakeError> {
+        self.claim()?;
+        Ok(Locked { inner: self })
+    }
+}
+
+impl<T: Send> Source for Inner<T> {
+    fn state(&self) -> HandOffState {
+        Inner::state(self)
+    }
+
+    fn taken(&self) -> Option<Result<(), TakeError>> {
+        Inner::taken(self)
+    }
+}
+
+impl<T: Debug> Inner<T> {
+    /// Formats the value, if it is still in the slot, as a field of a struct
+    /// named `name`.
+    pub(crate) fn fmt_debug(&self, name: &str, fmt: &mut Formatter) -> FmtResult {
+        let mut builder = fmt.debug_struct(name);
+
+        let locked_value = self.lock();
+        match locked_value {
+            Ok(val) => {
+                builder.field("value", &Some(&*val));
+            },
+            Err(_) => {
+                builder.field("value", &None::<T>);
+            }
+        }
+
+        #[cfg(feature = "diagnostics")]
+        if let Some(by) = self.taken_by() {
+            builder.field("taken_by", &by);
+        }
+
+        builder.finish()
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() != UNTAKEN {
+            return;
+        }
+
+        match self.on_unclaimed.take() {
+            Some(on_unclaimed) => {
+                // SAFETY: The value was never moved out of the slot, and the
+                // slot is not accessed again after this read.
+                on_unclaimed(unsafe { self.value.get_mut().assume_init_read() });
+            },
+            None => {
+                // SAFETY: The value was never moved out of the slot.
+                unsafe { self.value.get_mut().assume_init_drop() };
+            },
+        }
+    }
+}
+
+/// Publishes the outcome of `init` with `Inner::finish_init` when dropped,
+/// including while unwinding.
+struct Publish<'a, T> {
+    inner: &'a Inner<T>,
+    state: u8,
+}
+
+impl<'a, T> Publish<'a, T> {
+    fn new(inner: &'a Inner<T>, state: u8) -> Self {
+        Self { inner, state }
+    }
+}
+
+impl<T> Drop for Publish<'_, T> {
+    fn drop(&mut self) {
+        self.inner.finish_init(self.state);
+    }
+}
+
+/// Exclusive access to a value that is still in its slot, held as a claim.
+///
+/// The value is put back and made available again when the guard is dropped,
+/// even if the holder panics, or dropped if the handoff was cancelled in the
+/// meantime.
+pub(crate) struct Locked<'a, T> {
+    inner: &'a Inner<T>,
+}
+
+impl<T> Locked<'_, T> {
+    /// Moves the locked value out of the slot, marking it as taken.
+    pub(crate) fn take(self) -> T {
+        let inner = self.inner;
+        std::mem::forget(self);
+        inner.take_claimed()
+    }
+}
+
+impl<T> Deref for Locked<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The guard holds the claim on the value.
+        unsafe { (*self.inner.value.get()).assume_init_ref() }
+    }
+}
+
+impl<T> DerefMut for Locked<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The guard holds the claim on the value.
+        unsafe { (*self.inner.value.get()).assume_init_mut() }
+    }
+}
+
+impl<T> Drop for Locked<'_, T> {
+    fn drop(&mut self) {
+        self.inner.release_claim();
+    }
+}
@@ -0,0 +1,59 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::{ InitError, TakeError };
+
+/// What a lazy `HandOff` does when its initializer fails.
+///
+/// Passed to [`HandOff::try_lazy`](crate::HandOff::try_lazy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LazyPolicy {
+    /// Keep the error: every later taker fails with the same
+    /// [`TakeError::InitFailed`](crate::TakeError::InitFailed) and the
+    /// initializer never runs again.
+    Poison,
+    /// Forget the error: the next taker runs the initializer again.
+    Retry,
+}
+
+/// An initializer that is awaited by the task that wins the slot.
+pub(crate) type AsyncInit<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Produces the value of a lazy slot for the taker that wins it.
+pub(crate) enum Init<T> {
+    /// Runs once. The value is considered taken even if it panics.
+    Once(Box<dyn FnOnce() -> T + Send>),
+    /// May fail, in which case `policy` decides whether it runs again.
+    Fallible {
+        init: Box<dyn FnMut() -> Result<T, InitError> + Send>,
+        policy: LazyPolicy,
+    },
+    /// Takes the value from the source of a mapped handoff, which reports its
+    /// own errors. Only blocks on an async initializer of the source in
+    /// `InitMode::Block`.
+    Mapped(Box<dyn FnMut(InitMode) -> Result<T, TakeError> + Send>),
+    /// Awaited by the winner, which may hand it back unfinished.
+    Async(AsyncInit<T>),
+}
+
+/// How a taker deals with an async initializer, which it cannot finish
+/// without waiting.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InitMode {
+    /// Leave the slot alone and fail with `TakeError::Lazy`.
+    Try,
+    /// Take the initializer, to be polled by an async taker.
+    Async,
+    /// Take the initializer, to be blocked on by a blocking taker.
+    Block,
+}
+
+/// What a taker got out of a slot.
+pub(crate) enum Took<T> {
+    Value(T),
+    /// The slot is lazy with an async initializer. The taker owns the slot
+    /// until it finishes the initializer with `Inner::poll_init` or hands it
+    /// back with `Inner::abort_init`.
+    Init(AsyncInit<T>),
+}
@@ -0,0 +1,116 @@
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::{ Deref, DerefMut };
+use std::sync::Arc;
+
+use crate::inner::Inner;
+
+/// Temporary ownership of the value of a `HandOff`.
+///
+/// Created by [`HandOff::lease`](crate::HandOff::lease). While the lease is
+/// alive, other handles see the value as claimed and cannot take it. Dropping
+/// the lease puts the value back into the `HandOff` and wakes the handles
+/// waiting for it, unless it was kept with [`Lease::consume`].
+///
+/// If the `HandOff` is cancelled while the value is leased, the lease keeps
+/// the value until it ends, and the value is dropped instead of being put
+/// back.
+///
+/// # Example
+/// ```
+/// use takeit::HandOff;
+///
+/// let connection = HandOff::new(vec![0u8; 4]);
+///
+/// {
+///     let mut lease = connection.lease().unwrap();
+///     lease[0] = 1;
+///     assert!(connection.lease().is_none());
+/// }
+///
+/// assert_eq!(connection.take(), Some(vec![1, 0, 0, 0]));
+/// ```
+///
+/// Like a `MutexGuard`, a lease hands out `&T`, so it can only be shared
+/// between threads if `T` is `Sync`:
+///
+/// ```compile_fail
+/// use std::cell::Cell;
+/// use takeit::Lease;
+///
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<Lease<Cell<u64>>>();
+/// ```
+pub struct Lease<T> {
+    inner: Arc<Inner<T>>,
+    /// Opts out of the auto traits, which only need `T: Send` through
+    /// `Inner`. The manual impls below add the bounds of a `MutexGuard`.
+    _not_send_sync: PhantomData<*const T>,
+}
+
+// SAFETY: Moving the lease to another thread moves the exclusive access to
+// the value with it.
+unsafe impl<T: Send> Send for Lease<T> {}
+// SAFETY: A shared lease only hands out `&T`, which is fine to share between
+// threads when `T: Sync`.
+unsafe impl<T: Send + Sync> Sync for Lease<T> {}
+
+impl<T> Lease<T> {
+    pub(crate) fn new(inner: Arc<Inner<T>>) -> Self {
+        Self { inner, _not_send_sync: PhantomData }
+    }
+
+    /// Keeps the leased value instead of putting it back, marking the value
+    /// of the `HandOff` as taken.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let lease = handoff.lease().unwrap();
+    ///
+    /// assert_eq!(lease.consume(), 10);
+    /// assert!(handoff.is_taken());
+    /// ```
+    pub fn consume(self) -> T {
+        let this = ManuallyDrop::new(self);
+        let val = this.inner.take_claimed();
+
+        // SAFETY: `this` is never used or dropped again, so the handle is
+        // released exactly once.
+        drop(unsafe { std::ptr::read(&this.inner) });
+        val
+    }
+}
+
+impl<T> Deref for Lease<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The lease holds the claim on the value until it is dropped
+        // or consumed.
+        unsafe { &*self.inner.slot() }
+    }
+}
+
+impl<T> DerefMut for Lease<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The lease holds the claim on the value until it is dropped
+        // or consumed.
+        unsafe { &mut *self.inner.slot() }
+    }
+}
+
+impl<T> Drop for Lease<T> {
+    fn drop(&mut self) {
+        self.inner.release_claim();
+    }
+}
+
+impl<T: Debug> Debug for Lease<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("Lease").field("value", &**self).finish()
+    }
+}
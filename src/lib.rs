@@ -1,13 +1,57 @@
-use std::sync::{ Arc, Mutex };
+use std::sync::Arc;
 use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::future::Future;
+use std::mem::ManuallyDrop;
+use std::panic::{ RefUnwindSafe, UnwindSafe };
+use std::time::{ Duration, Instant };
+
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod error;
+mod future;
+mod giver;
+mod inner;
+mod lazy;
+mod lease;
+mod pool;
+mod reply;
+mod reservation;
+mod select;
+mod state;
+mod taker;
+mod transaction;
+mod waiters;
+
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::TakenBy;
+pub use error::{ InitError, TakeError };
+pub use future::{ TakeFuture, TakenFuture };
+pub use giver::Giver;
+pub use lazy::LazyPolicy;
+pub use lease::Lease;
+pub use pool::HandOffPool;
+pub use reply::{ Reply, ReplyError, ReplyFuture, ReplyReceiver };
+pub use reservation::Reservation;
+pub use select::TakeAnyFuture;
+pub use state::HandOffState;
+pub use taker::Taker;
+pub use transaction::TakeAll;
+
+use inner::Inner;
+use lazy::Init;
 
 /// A syncing type for sending a single object.
 /// 
-/// The `HandOff` is initialized with a value on creation. The handoff can then
-/// be cloned and sent between threads.
+/// The `HandOff` is initialized with a value on creation, or created empty and
+/// filled later by a producer. The handoff can then be cloned and sent between
+/// threads.
 /// The first thread to take the value, receives it and takes ownership over the
 /// value. After the value was taken once, trying to take it again is not allowed.
 ///
+/// Taking is lock-free: the handoff keeps an atomic state next to the value,
+/// so racing takers settle the winner with a single compare-and-swap and the
+/// losers return right away instead of blocking.
+///
 /// # Example
 /// ```
 /// use takeit::HandOff;
@@ -47,7 +91,7 @@ use std::fmt::{ Debug, Formatter, Result as FmtResult };
 ///     thread2.join().unwrap();
 /// }
 /// ```
-pub struct HandOff<T>(Arc<Mutex<Option<T>>>);
+pub struct HandOff<T>(Arc<Inner<T>>);
 
 impl<T> HandOff<T> {
     /// Creates a new HandOff object initialized with a value of type `T`
@@ -60,9 +104,380 @@ impl<T> HandOff<T> {
     /// let handoff2 = HandOff::new(String::from("Hello, World!"));
     /// ```
     pub fn new(val: T) -> Self {
-        Self(Arc::new(Mutex::new(Some(val))))
+        Self::from_inner(Arc::new(Inner::new(val)))
+    }
+
+    /// Creates a handoff initialized with a value of type `T`, split into a
+    /// [`Giver`] for its originator and a cloneable [`Taker`] for consumers.
+    ///
+    /// Only takers can take the value, and only the giver can fill, cancel or
+    /// reclaim it.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let (giver, taker) = HandOff::pair(10);
+    ///
+    /// assert_eq!(taker.clone().take(), Some(10));
+    /// assert!(giver.reclaim().is_err());
+    /// ```
+    pub fn pair(val: T) -> (Giver<T>, Taker<T>) {
+        let giver = Giver::new(val);
+        let taker = giver.taker();
+        (giver, taker)
+    }
+
+    /// Creates an empty handoff split into a [`Giver`] and a [`Taker`], like
+    /// [`HandOff::pair`]. The value is provided later with [`Giver::fill`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let (giver, taker) = HandOff::empty_pair();
+    /// let worker = std::thread::spawn(move || taker.take_blocking());
+    ///
+    /// giver.fill(10).unwrap();
+    /// assert_eq!(worker.join().unwrap(), Some(10));
+    /// ```
+    pub fn empty_pair() -> (Giver<T>, Taker<T>) {
+        let giver = Giver::empty();
+        let taker = giver.taker();
+        (giver, taker)
+    }
+
+    /// Creates a new HandOff object initialized with a value of type `T`, that
+    /// hands the value to `on_unclaimed` if every handle is dropped before any
+    /// of them took it.
+    ///
+    /// The callback runs in the thread dropping the last handle, which lets an
+    /// unclaimed value be re-queued or logged instead of silently dropped. It
+    /// does not run if the value was taken, reclaimed or extracted with
+    /// [`HandOff::into_inner`].
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::mpsc;
+    /// use takeit::HandOff;
+    ///
+    /// let (requeue, orphans) = mpsc::channel();
+    /// let handoff = HandOff::with_on_unclaimed(10, move |val| requeue.send(val).unwrap());
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// drop(handoff);
+    /// drop(handoff_clone);
+    /// assert_eq!(orphans.try_recv(), Ok(10));
+    /// ```
+    pub fn with_on_unclaimed(val: T, on_unclaimed: impl FnOnce(T) + Send + 'static) -> Self {
+        Self::from_inner(Arc::new(Inner::with_on_unclaimed(val, Box::new(on_unclaimed))))
+    }
+
+    /// Creates a new HandOff object whose value is bundled with a one-shot
+    /// [`Reply`], so that whoever takes the value can answer the originator.
+    ///
+    /// The originator waits for the answer with the returned
+    /// [`ReplyReceiver`]. If the taker drops the reply without answering, or
+    /// nobody takes the value at all, receiving fails with
+    /// [`ReplyError::Dropped`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let (handoff, receiver) = HandOff::with_reply::<i32>(20);
+    ///
+    /// let worker = std::thread::spawn(move || {
+    ///     let (val, reply) = handoff.take().unwrap();
+    ///     reply.send(val + 1).unwrap();
+    /// });
+    ///
+    /// assert_eq!(receiver.recv(), Ok(21));
+    /// worker.join().unwrap();
+    /// ```
+    pub fn with_reply<R>(val: T) -> (HandOff<(T, Reply<R>)>, ReplyReceiver<R>) {
+        let (reply, receiver) = Reply::new();
+        (HandOff::new((val, reply)), receiver)
+    }
+
+    /// Creates a new HandOff object whose value is produced by `init` in the
+    /// thread that takes it.
+    ///
+    /// `init` runs at most once, and only if some handle takes the value: the
+    /// other takers fail right away without running it, and nothing is built
+    /// if nobody claims the value. Since the value does not exist before it is
+    /// taken, it cannot be inspected, leased or reserved, which fail with
+    /// [`TakeError::Lazy`].
+    ///
+    /// If `init` panics, the value is considered taken.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let config = HandOff::lazy(|| String::from("parsed config"));
+    /// let config_clone = config.clone();
+    ///
+    /// assert_eq!(config.take(), Some(String::from("parsed config")));
+    /// assert_eq!(config_clone.take(), None);
+    /// ```
+    pub fn lazy(init: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self::from_inner(Arc::new(Inner::lazy(Init::Once(Box::new(init)))))
+    }
+
+    /// Creates a new HandOff object whose value is produced by the fallible
+    /// `init` in the thread that takes it.
+    ///
+    /// Like with [`HandOff::lazy`], only the taker that wins the value runs
+    /// `init`. While it runs, the other takers fail with
+    /// [`TakeError::Claimed`], and blocking takers wait for its outcome. If
+    /// `init` fails, the taker that ran it gets [`TakeError::InitFailed`] and
+    /// `policy` decides what happens next:
+    ///
+    /// - [`LazyPolicy::Poison`] keeps the error, and every later taker fails
+    ///   with the same [`TakeError::InitFailed`].
+    /// - [`LazyPolicy::Retry`] lets the next taker run `init` again, which is
+    ///   why `init` is `FnMut` rather than `FnOnce`.
+    ///
+    /// If `init` panics, the value is considered taken.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{ Error, ErrorKind };
+    /// use takeit::{ HandOff, HandOffState, LazyPolicy, TakeError };
+    ///
+    /// let connection = HandOff::<String>::try_lazy(LazyPolicy::Poison, || {
+    ///     Err(Error::from(ErrorKind::ConnectionRefused))
+    /// });
+    /// let connection_clone = connection.clone();
+    ///
+    /// let first = connection.try_take().unwrap_err();
+    /// assert!(matches!(first, TakeError::InitFailed { .. }));
+    /// assert_eq!(connection_clone.try_take().unwrap_err(), first);
+    /// assert_eq!(connection.state(), HandOffState::Poisoned);
+    /// ```
+    pub fn try_lazy<E>(
+        policy: LazyPolicy,
+        mut init: impl FnMut() -> Result<T, E> + Send + 'static,
+    ) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let init = Box::new(move || init().map_err(InitError::new));
+        Self::from_inner(Arc::new(Inner::lazy(Init::Fallible { init, policy })))
+    }
+
+    /// Creates a new HandOff object whose value is produced by awaiting
+    /// `init` in the task that takes it.
+    ///
+    /// `init` is only polled by the [`TakeFuture`] that wins the value, or by
+    /// a blocking taker such as [`HandOff::take_blocking`], which blocks the
+    /// thread until `init` completes. While it is pending, the other takers
+    /// fail with [`TakeError::Claimed`], and blocking or async takers wait for
+    /// it. If the winning future is dropped before `init` completes, `init` is
+    /// kept in the handoff and the next taker continues it.
+    ///
+    /// Takers that never wait, such as [`HandOff::try_take`] or
+    /// [`HandOff::take_any`], leave `init` alone and fail with
+    /// [`TakeError::Lazy`].
+    ///
+    /// If `init` panics, the value is considered taken.
+    ///
+    /// # Example
+    /// ```
+    /// use std::future::Future;
+    /// use std::task::{ Context, Poll, Waker };
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::lazy_async(async { 6 * 7 });
+    /// let mut future = std::pin::pin!(handoff.clone().take_async());
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some(42)));
+    /// assert!(handoff.is_taken());
+    /// ```
+    pub fn lazy_async(init: impl Future<Output = T> + Send + 'static) -> Self {
+        Self::from_inner(Arc::new(Inner::lazy(Init::Async(Box::pin(init)))))
+    }
+
+    /// Turns the handoff into a handoff of `U` that shares the value with
+    /// every clone of `self`.
+    ///
+    /// The value is still taken at most once: whichever handle of either
+    /// handoff wins gets it, and `f` only runs if the winner is a handle of
+    /// the mapped handoff. Until then, the mapped handoff reports the state of
+    /// `self`, and its takers wait for `self` to be filled or released. Like
+    /// with [`HandOff::lazy`], the value of the mapped handoff cannot be
+    /// inspected, leased or reserved.
+    ///
+    /// Cancelling the mapped handoff only cancels the mapped handles, and
+    /// leaves the value to the handles of `self`.
+    ///
+    /// If `self` was created with [`HandOff::lazy_async`], its initializer is
+    /// only run by blocking takers of the mapped handoff, such as
+    /// [`HandOff::take_blocking`]. The other takers fail with
+    /// [`TakeError::Lazy`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(20);
+    /// let handoff_clone = handoff.clone();
+    /// let message = handoff.map(|val| format!("got {val}"));
+    ///
+    /// assert_eq!(message.take(), Some(String::from("got 20")));
+    /// assert_eq!(handoff_clone.take(), None);
+    /// ```
+    pub fn map<U>(self, f: impl FnOnce(T) -> U + Send + 'static) -> HandOff<U>
+    where
+        T: Send + 'static,
+    {
+        let source = self.into_inner_arc();
+        let taken = source.clone();
+        let mut f = Some(f);
+        let init = move |mode| {
+            let val = taken.take_with(mode)?;
+            Ok(f.take().expect("mapped value produced twice")(val))
+        };
+
+        HandOff::from_inner(Arc::new(Inner::mapped(Init::Mapped(Box::new(init)), source)))
+    }
+
+    /// Creates a new HandOff object without a value.
+    ///
+    /// The handoff can be cloned and distributed to consumers right away, and
+    /// the value can be provided later with [`HandOff::fill`]. Until then,
+    /// taking fails with [`TakeError::Empty`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::<i32>::empty();
+    ///
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Empty));
+    /// ```
+    pub fn empty() -> Self {
+        Self::from_inner(Arc::new(Inner::empty()))
+    }
+
+    /// Fills an empty `HandOff` with a value, making it available to every
+    /// clone.
+    ///
+    /// # Errors
+    /// If the `HandOff` already holds a value, its value was already taken and
+    /// the `HandOff` is not [reusable](HandOff::reusable), or it was
+    /// cancelled, `val` is handed back in `Err`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::empty();
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// assert_eq!(handoff.fill(1), Ok(()));
+    /// assert_eq!(handoff.fill(2), Err(2));
+    /// assert_eq!(handoff_clone.take(), Some(1));
+    /// ```
+    pub fn fill(&self, val: T) -> Result<(), T> {
+        self.0.fill(val).map(|_| ())
     }
     
+    /// Creates a new empty HandOff object that can be filled again every time
+    /// its value is taken.
+    ///
+    /// Every value moved into a reusable `HandOff` gets the next generation
+    /// number, starting at 1, so a long-lived slot can serve one request after
+    /// the other without allocating a new `HandOff` each time. Handles that
+    /// must only take the value they were given can use
+    /// [`HandOff::take_generation`], so a stale handle cannot steal a later
+    /// value.
+    ///
+    /// Once its value is taken, a reusable `HandOff` waits to be filled again:
+    /// [`HandOff::take_blocking`], [`HandOff::take_async`] and their variants
+    /// keep waiting for the next value instead of giving up.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let slot = HandOff::reusable();
+    ///
+    /// assert_eq!(slot.fill_generation("first"), Ok(1));
+    /// assert_eq!(slot.try_take(), Ok("first"));
+    /// assert_eq!(slot.fill_generation("second"), Ok(2));
+    /// assert_eq!(slot.try_take(), Ok("second"));
+    /// ```
+    pub fn reusable() -> Self {
+        Self::from_inner(Arc::new(Inner::reusable()))
+    }
+
+    /// Fills the `HandOff` like [`HandOff::fill`], returning the generation
+    /// number of the new value.
+    ///
+    /// # Errors
+    /// If the `HandOff` cannot be filled, `val` is handed back in `Err`. See
+    /// [`HandOff::fill`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let slot = HandOff::reusable();
+    ///
+    /// assert_eq!(slot.fill_generation(10), Ok(1));
+    /// assert_eq!(slot.fill_generation(20), Err(20));
+    /// ```
+    pub fn fill_generation(&self, val: T) -> Result<u64, T> {
+        self.0.fill(val)
+    }
+
+    /// Returns the generation number of the value in the `HandOff`, or of its
+    /// last value if it was taken. Returns 0 if the `HandOff` was never filled.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let slot = HandOff::reusable();
+    /// assert_eq!(slot.generation(), 0);
+    ///
+    /// slot.fill(10).unwrap();
+    /// assert_eq!(slot.generation(), 1);
+    /// ```
+    pub fn generation(&self) -> u64 {
+        self.0.generation()
+    }
+
+    /// Attempts to move the value out of the `HandOff` only if it belongs to
+    /// `generation`.
+    ///
+    /// The generation is checked and the value is taken in a single critical
+    /// section, so a handle holding on to an old generation number can never
+    /// take a value that was filled after its own was taken.
+    ///
+    /// # Errors
+    /// Returns [`TakeError::Stale`] if the `HandOff` holds a value of another
+    /// generation. Otherwise fails like [`HandOff::try_take`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let slot = HandOff::reusable();
+    /// let first = slot.fill_generation("first").unwrap();
+    /// slot.try_take().unwrap();
+    ///
+    /// let second = slot.fill_generation("second").unwrap();
+    /// assert_eq!(slot.take_generation(first), Err(TakeError::Stale));
+    /// assert_eq!(slot.take_generation(second), Ok("second"));
+    /// ```
+    pub fn take_generation(&self, generation: u64) -> Result<T, TakeError> {
+        self.0.take_generation(generation)
+    }
+
     /// Returns the value of the `HandOff` by moving it.
     ///
     /// # Errors
@@ -79,70 +494,1451 @@ impl<T> HandOff<T> {
     /// assert_eq!(handoff_clone.take(), None);
     /// ```
     pub fn take(self) -> Option<T> {
-        if let Ok(mut locked_value) = self.0.lock() {
-            locked_value.take()
-        } else {
-            None
-        }
+        self.try_take().ok()
     }
-}
 
-impl<T> Clone for HandOff<T> {
-    fn clone(&self) -> Self {
-        Self(self.0.clone())
+    /// Attempts to move the value out of the `HandOff` without consuming the
+    /// handle, reporting why it could not be taken on failure.
+    ///
+    /// Unlike [`HandOff::take`], the handle stays usable after a failed
+    /// attempt, so a single long-lived handle can retry, e.g. while waiting for
+    /// an empty `HandOff` to be filled.
+    ///
+    /// # Errors
+    /// Returns [`TakeError::Taken`] if the value was already taken earlier,
+    /// [`TakeError::Empty`] if the `HandOff` was not filled yet, or
+    /// [`TakeError::Cancelled`] if the `HandOff` was cancelled. Since it never
+    /// waits, it returns [`TakeError::Lazy`] rather than run the initializer
+    /// of a [`HandOff::lazy_async`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::empty();
+    ///
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Empty));
+    /// handoff.fill(1337).unwrap();
+    /// assert_eq!(handoff.try_take(), Ok(1337));
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Taken));
+    /// ```
+    pub fn try_take(&self) -> Result<T, TakeError> {
+        self.0.take()
     }
-}
 
-impl<T: Debug> Debug for HandOff<T> {
-    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
-        let mut builder = fmt.debug_struct("HandOff");
-
-        let locked_value = self.0.lock();
-        match locked_value {
-            Ok(val) => {
-                builder.field("value", &val);
-            },
-            _ => {
-                builder.field("value", &None::<T>);
-            }
-        }
+    /// Cancels the `HandOff`, returning its value if nobody took it yet.
+    ///
+    /// Afterwards, taking from any clone fails with [`TakeError::Cancelled`]
+    /// and filling hands the value back. Threads and tasks waiting for the
+    /// `HandOff` to be filled are woken immediately. Cancelling a `HandOff`
+    /// whose value was already taken does nothing, unless it is
+    /// [reusable](HandOff::reusable): then it is waiting for its next value,
+    /// and is cancelled like an empty `HandOff`.
+    ///
+    /// If a taker is running the initializer of a lazy `HandOff`, the
+    /// `HandOff` reports itself as cancelled right away, and is cancelled for
+    /// good once the initializer fails or is handed back. An initializer that
+    /// produces the value still hands it to its taker.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// assert_eq!(handoff.cancel(), Some(10));
+    /// assert_eq!(handoff_clone.try_take(), Err(TakeError::Cancelled { reason: None }));
+    /// ```
+    pub fn cancel(&self) -> Option<T> {
+        self.0.cancel(None)
+    }
 
-        builder.finish()
+    /// Cancels the `HandOff` like [`HandOff::cancel`], recording a reason that
+    /// takers receive in [`TakeError::Cancelled`].
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::<i32>::empty();
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// assert_eq!(handoff.cancel_with("request aborted"), None);
+    /// assert_eq!(
+    ///     handoff_clone.try_take(),
+    ///     Err(TakeError::Cancelled { reason: Some("request aborted".into()) }),
+    /// );
+    /// ```
+    pub fn cancel_with(&self, reason: impl Into<Arc<str>>) -> Option<T> {
+        self.0.cancel(Some(reason.into()))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    #[derive(PartialEq, Debug)]
-    struct Foo {
-        val: i32,
+    /// Returns the value if this is the only handle left and nobody took the
+    /// value, in the spirit of [`Arc::try_unwrap`].
+    ///
+    /// Since no other handle exists, nobody else can take the value anymore,
+    /// so the originator of a `HandOff` can deterministically get back a value
+    /// that no consumer claimed.
+    ///
+    /// # Errors
+    /// If other handles are still alive, the `HandOff` is handed back in
+    /// `Err`. It is also handed back if the value was already taken, the
+    /// `HandOff` was never filled, or it is lazy or mapped, since reclaiming
+    /// never runs an initializer.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(3);
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// let handoff = handoff.reclaim().unwrap_err();
+    /// drop(handoff_clone);
+    /// assert_eq!(handoff.reclaim().ok(), Some(3));
+    /// ```
+    pub fn reclaim(self) -> Result<T, Self> {
+        if Arc::strong_count(&self.0) != 1 {
+            return Err(self);
+        }
+
+        let taken = self.0.lock().map(|locked_value| locked_value.take());
+        taken.map_err(|_| self)
     }
 
-    #[test]
-    fn test_single_thread() {
-        let handoff = HandOff::new(19);
-        let handoff_clone = handoff.clone();
-        
-        assert_eq!(handoff.take(), Some(19));
-        assert_eq!(handoff_clone.take(), None);
+    /// Returns the value if this is the last handle and nobody took the value,
+    /// in the spirit of [`Arc::into_inner`].
+    ///
+    /// If every handle of a `HandOff` calls `into_inner` instead of being
+    /// dropped, exactly one of them receives the untaken value, even when the
+    /// calls race.
+    ///
+    /// # Errors
+    /// If other handles are still alive, the value was already taken, or the
+    /// `HandOff` was never filled, it returns `None`. A lazy or mapped
+    /// `HandOff` also returns `None`, since its initializer is never run here.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(3);
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// assert_eq!(handoff.into_inner(), None);
+    /// assert_eq!(handoff_clone.into_inner(), Some(3));
+    /// ```
+    pub fn into_inner(self) -> Option<T> {
+        Arc::into_inner(self.into_inner_arc()).and_then(Inner::into_value)
     }
-    
-    #[test]
-    fn test_non_clonable() {
-        let handoff = HandOff::new(Foo { val: 10 });
-        let handoff_clone = handoff.clone();
 
-        assert_eq!(handoff.take(), Some(Foo { val: 10 }))
+    /// Returns the current state of the `HandOff`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, HandOffState };
+    ///
+    /// let handoff = HandOff::empty();
+    /// assert_eq!(handoff.state(), HandOffState::Empty);
+    ///
+    /// handoff.fill(3).unwrap();
+    /// assert_eq!(handoff.state(), HandOffState::Available);
+    ///
+    /// handoff.clone().take();
+    /// assert_eq!(handoff.state(), HandOffState::Taken);
+    /// ```
+    pub fn state(&self) -> HandOffState {
+        self.0.state()
     }
-    
-    #[test]
-    fn test_threads() {
-        let handoff = HandOff::new(Foo { val: 42 });
 
-        let my_thread = std::thread::spawn(move || {
-            assert_eq!(handoff.clone().take(), Some(Foo { val: 42 }))
-        });
+    /// Returns `true` if the value of the `HandOff` was taken by some handle.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(3);
+    /// assert!(!handoff.is_taken());
+    ///
+    /// handoff.clone().take();
+    /// assert!(handoff.is_taken());
+    /// ```
+    pub fn is_taken(&self) -> bool {
+        self.state() == HandOffState::Taken
+    }
+
+    /// Returns the record of the take that won the value: the thread that
+    /// took it, when, and the backtrace of the call.
+    ///
+    /// Only available with the `diagnostics` feature. Returns `None` while the
+    /// value was not taken, and after a reusable `HandOff` is filled again.
+    /// Handles that lost the race get a plain [`TakeError::Taken`] and can look
+    /// the record up here. It is also shown by the `Debug` output of the
+    /// `HandOff`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(3);
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// std::thread::Builder::new()
+    ///     .name(String::from("worker"))
+    ///     .spawn(move || handoff_clone.take())
+    ///     .unwrap()
+    ///     .join()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(handoff.taken_by().unwrap().thread_name(), Some("worker"));
+    /// ```
+    #[cfg(feature = "diagnostics")]
+    pub fn taken_by(&self) -> Option<TakenBy> {
+        self.0.taken_by()
+    }
+
+    /// Returns `true` if the `HandOff` holds a value that can be taken.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::empty();
+    /// assert!(!handoff.is_available());
+    ///
+    /// handoff.fill(3).unwrap();
+    /// assert!(handoff.is_available());
+    /// ```
+    pub fn is_available(&self) -> bool {
+        self.state() == HandOffState::Available
+    }
+
+    /// Returns the number of handles sharing this `HandOff`, including this
+    /// one.
+    ///
+    /// Every clone of the `HandOff` and every [`Taker`] counts, including the
+    /// ones moved into a pending [`TakeFuture`]. The [`Giver`], live
+    /// [`Lease`]s and [`Reservation`]s, and handoffs created with
+    /// [`HandOff::map`] do not count as handles of this `HandOff`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(3);
+    /// let handoff_clone = handoff.clone();
+    /// assert_eq!(handoff.handle_count(), 2);
+    ///
+    /// drop(handoff_clone);
+    /// assert_eq!(handoff.handle_count(), 1);
+    /// ```
+    pub fn handle_count(&self) -> usize {
+        self.0.handle_count()
+    }
+
+    /// Blocks the current thread until the value of the `HandOff` was taken by
+    /// some handle.
+    ///
+    /// This lets a producer wait until a consumer actually owns the value
+    /// before it proceeds.
+    ///
+    /// # Errors
+    /// Returns [`TakeError::Cancelled`] if the `HandOff` is cancelled instead,
+    /// since its value will never be taken.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// let consumer = std::thread::spawn(move || handoff_clone.take());
+    ///
+    /// assert_eq!(handoff.wait_taken(), Ok(()));
+    /// assert_eq!(consumer.join().unwrap(), Some(10));
+    /// ```
+    pub fn wait_taken(&self) -> Result<(), TakeError> {
+        self.0.wait_taken(None)
+    }
+
+    /// Blocks the current thread for at most `timeout` until the value of the
+    /// `HandOff` was taken by some handle.
+    ///
+    /// # Errors
+    /// Returns [`TakeError::TimedOut`] if nobody took the value in time, or
+    /// [`TakeError::Cancelled`] if the `HandOff` is cancelled instead.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::new(10);
+    ///
+    /// assert_eq!(
+    ///     handoff.wait_taken_timeout(Duration::from_millis(10)),
+    ///     Err(TakeError::TimedOut),
+    /// );
+    /// ```
+    pub fn wait_taken_timeout(&self, timeout: Duration) -> Result<(), TakeError> {
+        self.0.wait_taken(Some(Instant::now() + timeout))
+    }
+
+    /// Returns a future that resolves once the value of the `HandOff` was
+    /// taken by some handle. See [`HandOff::wait_taken`].
+    ///
+    /// # Errors
+    /// The future resolves to [`TakeError::Cancelled`] if the `HandOff` is
+    /// cancelled instead.
+    ///
+    /// # Example
+    /// ```
+    /// use std::future::Future;
+    /// use std::task::{ Context, Poll, Waker };
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let mut future = std::pin::pin!(handoff.wait_taken_async());
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+    /// handoff.clone().take();
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    /// ```
+    pub fn wait_taken_async(&self) -> TakenFuture<'_, T> {
+        TakenFuture::new(&self.0)
+    }
+
+    /// Runs `f` on a reference to the value while it is still in the
+    /// `HandOff`, without taking it.
+    ///
+    /// While `f` runs, the value counts as claimed, like with a [`Lease`]:
+    /// taking it fails with [`TakeError::Claimed`], blocking or async takers
+    /// wait until `f` returns, and cancelling takes effect once `f` returns.
+    /// This holds for `f` itself too. If `f` panics the value is left in
+    /// place.
+    ///
+    /// # Errors
+    /// If the value was already taken, or the `HandOff` was not filled yet, `f`
+    /// is not called and `None` is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(String::from("Hello, World!"));
+    ///
+    /// assert_eq!(handoff.with_ref(|val| val.len()), Some(13));
+    /// assert_eq!(handoff.clone().take(), Some(String::from("Hello, World!")));
+    /// assert_eq!(handoff.with_ref(|val| val.len()), None);
+    /// ```
+    pub fn with_ref<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.0.lock().ok().map(|val| f(&val))
+    }
+
+    /// Runs `f` on a mutable reference to the value while it is still in the
+    /// `HandOff`, without taking it.
+    ///
+    /// While `f` runs, the value counts as claimed, like with
+    /// [`HandOff::with_ref`]. If `f` panics the value is left in place,
+    /// including any changes made before the panic.
+    ///
+    /// # Errors
+    /// If the value was already taken, or the `HandOff` was not filled yet, `f`
+    /// is not called and `None` is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(vec![1, 2]);
+    ///
+    /// handoff.with_mut(|val| val.push(3));
+    /// assert_eq!(handoff.take(), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.0.lock().ok().map(|mut val| f(&mut val))
+    }
+
+    /// Moves the value out of the `HandOff` only if `pred` returns `true` for
+    /// it.
+    ///
+    /// The value counts as claimed while `pred` runs, like with
+    /// [`HandOff::with_ref`], so no other handle can take the value in
+    /// between. If `pred` panics the value is left in place.
+    ///
+    /// # Errors
+    /// Returns `None` without taking the value if `pred` returns `false`. If
+    /// the value was already taken, or the `HandOff` was not filled yet, `pred`
+    /// is not called and `None` is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(5);
+    ///
+    /// assert_eq!(handoff.take_if(|val| *val > 10), None);
+    /// assert_eq!(handoff.take_if(|val| *val < 10), Some(5));
+    /// assert!(handoff.is_taken());
+    /// ```
+    pub fn take_if(&self, pred: impl FnOnce(&T) -> bool) -> Option<T> {
+        let locked_value = self.0.lock().ok()?;
+        if pred(&locked_value) {
+            Some(locked_value.take())
+        } else {
+            None
+        }
+    }
+
+    /// Temporarily moves the value out of reach of the other handles, returning
+    /// a [`Lease`] that puts it back when dropped.
+    ///
+    /// While the value is leased, taking it from another handle fails with
+    /// [`TakeError::Claimed`], and blocking or async takers wait for the lease
+    /// to end. Calling [`Lease::consume`] keeps the value for good.
+    ///
+    /// # Errors
+    /// If the value was already taken or leased, the `HandOff` was not filled
+    /// yet, or it was cancelled, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::new(String::from("token"));
+    ///
+    /// let lease = handoff.lease().unwrap();
+    /// assert_eq!(lease.as_str(), "token");
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Claimed));
+    ///
+    /// drop(lease);
+    /// assert_eq!(handoff.try_take(), Ok(String::from("token")));
+    /// ```
+    pub fn lease(&self) -> Option<Lease<T>> {
+        self.0.claim().ok()?;
+        Some(Lease::new(self.0.clone()))
+    }
+
+    /// Like [`HandOff::lease`], but blocks the current thread while the
+    /// `HandOff` is empty or its value is leased by another handle.
+    ///
+    /// # Errors
+    /// If the value was taken, or the `HandOff` was cancelled, it returns
+    /// `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let counter = HandOff::new(0);
+    ///
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let counter = counter.clone();
+    ///         std::thread::spawn(move || *counter.lease_blocking().unwrap() += 1)
+    ///     })
+    ///     .collect();
+    ///
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// assert_eq!(counter.take(), Some(4));
+    /// ```
+    pub fn lease_blocking(&self) -> Option<Lease<T>> {
+        self.0.claim_blocking(None).ok()?;
+        Some(Lease::new(self.0.clone()))
+    }
+
+    /// Reserves the value without taking it yet, returning a [`Reservation`]
+    /// that either commits to taking the value or aborts.
+    ///
+    /// While the value is reserved, other handles see it as claimed, so the
+    /// reserver can validate something (e.g. check a downstream quota) without
+    /// holding any lock. Dropping the reservation aborts it.
+    ///
+    /// # Errors
+    /// If the value was already taken or claimed, the `HandOff` was not filled
+    /// yet, or it was cancelled, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::new(10);
+    ///
+    /// let reservation = handoff.reserve().unwrap();
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Claimed));
+    /// assert_eq!(*reservation, 10);
+    ///
+    /// assert_eq!(reservation.commit(), 10);
+    /// assert_eq!(handoff.try_take(), Err(TakeError::Taken));
+    /// ```
+    pub fn reserve(&self) -> Option<Reservation<T>> {
+        self.lease().map(Reservation::new)
+    }
+
+    /// Returns the value of the `HandOff` by moving it, blocking the current
+    /// thread until the `HandOff` is filled.
+    ///
+    /// All the threads blocked on clones of the same `HandOff` are woken when
+    /// it is filled, and exactly one of them receives the value.
+    ///
+    /// # Errors
+    /// If the value was already taken, either before the call or by another
+    /// waiter, or the `HandOff` was cancelled, it returns `None`. A
+    /// [reusable](HandOff::reusable) `HandOff` whose value was taken keeps
+    /// waiting for the next value instead.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::empty();
+    /// let handoff_clone = handoff.clone();
+    ///
+    /// let consumer = std::thread::spawn(move || handoff_clone.take_blocking());
+    /// handoff.fill(42).unwrap();
+    ///
+    /// assert_eq!(consumer.join().unwrap(), Some(42));
+    /// ```
+    pub fn take_blocking(self) -> Option<T> {
+        self.0.take_blocking(None).ok()
+    }
+
+    /// Returns the value of the `HandOff` by moving it, blocking the current
+    /// thread for at most `timeout` until the `HandOff` is filled.
+    ///
+    /// # Errors
+    /// Returns [`TakeError::TimedOut`] if the `HandOff` was not filled in time,
+    /// [`TakeError::Taken`] if the value was taken by another handle, or
+    /// [`TakeError::Cancelled`] if the `HandOff` was cancelled. Like
+    /// [`HandOff::take_blocking`], a reusable `HandOff` keeps waiting for the
+    /// next value after its value was taken.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use takeit::{ HandOff, TakeError };
+    ///
+    /// let handoff = HandOff::<i32>::empty();
+    ///
+    /// assert_eq!(
+    ///     handoff.take_timeout(Duration::from_millis(10)),
+    ///     Err(TakeError::TimedOut),
+    /// );
+    /// ```
+    pub fn take_timeout(self, timeout: Duration) -> Result<T, TakeError> {
+        self.0.take_blocking(Some(Instant::now() + timeout))
+    }
+
+    /// Returns a future that resolves to the value of the `HandOff` once it is
+    /// filled.
+    ///
+    /// The future works on any executor: it only uses the [`Waker`] of the task
+    /// polling it. All the tasks waiting on clones of the same `HandOff` are
+    /// woken when it is filled, and exactly one of them receives the value.
+    /// Dropping the future before it resolves leaves the value in place.
+    ///
+    /// [`Waker`]: std::task::Waker
+    ///
+    /// # Errors
+    /// If the value was already taken, either before the call or by another
+    /// waiter, or the `HandOff` was cancelled, the future resolves to `None`.
+    /// A reusable `HandOff` whose value was taken keeps waiting for the next
+    /// value instead.
+    ///
+    /// # Example
+    /// ```
+    /// use std::future::Future;
+    /// use std::task::{ Context, Poll, Waker };
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::empty();
+    /// let mut future = std::pin::pin!(handoff.clone().take_async());
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+    /// handoff.fill(7).unwrap();
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some(7)));
+    /// ```
+    pub fn take_async(self) -> TakeFuture<T> {
+        TakeFuture::new(self)
+    }
+}
+
+impl<T: Clone> HandOff<T> {
+    /// Returns a clone of the value while it is still in the `HandOff`, without
+    /// taking it.
+    ///
+    /// # Errors
+    /// If the value was already taken, or the `HandOff` was not filled yet, it
+    /// returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    ///
+    /// assert_eq!(handoff.peek_cloned(), Some(10));
+    /// assert_eq!(handoff.clone().take(), Some(10));
+    /// assert_eq!(handoff.peek_cloned(), None);
+    /// ```
+    pub fn peek_cloned(&self) -> Option<T> {
+        self.with_ref(T::clone)
+    }
+}
+
+impl<T> HandOff<T> {
+    /// Wraps `inner` in a new handle, counted by [`HandOff::handle_count`].
+    pub(crate) fn from_inner(inner: Arc<Inner<T>>) -> Self {
+        inner.acquire_handle();
+        Self(inner)
+    }
+
+    /// Releases this handle and returns the state it shared.
+    fn into_inner_arc(self) -> Arc<Inner<T>> {
+        let this = ManuallyDrop::new(self);
+        this.0.release_handle();
+
+        // SAFETY: `this` is never used or dropped again, so the `Arc` is moved
+        // out exactly once.
+        unsafe { std::ptr::read(&this.0) }
+    }
+}
+
+impl<T> Clone for HandOff<T> {
+    fn clone(&self) -> Self {
+        Self::from_inner(self.0.clone())
+    }
+}
+
+impl<T> Drop for HandOff<T> {
+    fn drop(&mut self) {
+        self.0.release_handle();
+    }
+}
+
+impl<T: Debug> Debug for HandOff<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        self.0.fmt_debug("HandOff", fmt)
+    }
+}
+
+// Like a `Mutex`, a `HandOff` only hands out the value to one handle at a
+// time, so sharing it across `catch_unwind` is fine. `Taker` gets these
+// through the `HandOff` it wraps.
+impl<T> UnwindSafe for HandOff<T> {}
+impl<T> RefUnwindSafe for HandOff<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[derive(PartialEq, Debug)]
+    struct Foo {
+        val: i32,
+    }
+
+    #[test]
+    fn test_single_thread() {
+        let handoff = HandOff::new(19);
+        let handoff_clone = handoff.clone();
+        
+        assert_eq!(handoff.take(), Some(19));
+        assert_eq!(handoff_clone.take(), None);
+    }
+    
+    #[test]
+    #[allow(unused_variables)]
+    fn test_non_clonable() {
+        let handoff = HandOff::new(Foo { val: 10 });
+        let handoff_clone = handoff.clone();
+
+        assert_eq!(handoff.take(), Some(Foo { val: 10 }))
+    }
+    
+    #[test]
+    #[allow(unused_variables)]
+    fn test_threads() {
+        let handoff = HandOff::new(Foo { val: 42 });
+
+        let my_thread = std::thread::spawn(move || {
+            assert_eq!(handoff.clone().take(), Some(Foo { val: 42 }))
+        });
+    }
+
+    #[test]
+    fn test_contended_take() {
+        let handoff = HandOff::new(Foo { val: 7 });
+
+        let threads: Vec<_> = (0..16)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || handoff_clone.take())
+            })
+            .collect();
+        drop(handoff);
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners, vec![Foo { val: 7 }]);
+    }
+
+    #[test]
+    fn test_try_take_survives_panic() {
+        #[derive(PartialEq)]
+        struct PanicDebug(i32);
+
+        impl Debug for PanicDebug {
+            fn fmt(&self, _fmt: &mut Formatter) -> FmtResult {
+                panic!("panicked while formatting");
+            }
+        }
+
+        let handoff = HandOff::new(PanicDebug(3));
+        let handoff_clone = handoff.clone();
+
+        // The value is locked in place while it is formatted.
+        let panicking = std::thread::spawn(move || format!("{handoff_clone:?}"));
+        assert!(panicking.join().is_err());
+
+        let handoff_clone = handoff.clone();
+        assert!(handoff.try_take() == Ok(PanicDebug(3)));
+        assert_eq!(handoff_clone.try_take(), Err(TakeError::Taken));
+    }
+
+    #[test]
+    fn test_try_take_retry() {
+        struct Worker {
+            job: HandOff<Foo>,
+        }
+
+        let worker = Worker { job: HandOff::empty() };
+
+        assert_eq!(worker.job.try_take(), Err(TakeError::Empty));
+        worker.job.fill(Foo { val: 1 }).unwrap();
+        assert_eq!(worker.job.try_take(), Ok(Foo { val: 1 }));
+        assert_eq!(worker.job.try_take(), Err(TakeError::Taken));
+    }
+
+    #[test]
+    fn test_fill_threads() {
+        let handoff = HandOff::empty();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || loop {
+                    match handoff_clone.try_take() {
+                        Ok(val) => return Some(val),
+                        Err(TakeError::Empty) => std::thread::yield_now(),
+                        Err(_) => return None,
+                    }
+                })
+            })
+            .collect();
+
+        assert_eq!(handoff.fill(Foo { val: 8 }), Ok(()));
+        assert_eq!(handoff.fill(Foo { val: 9 }), Err(Foo { val: 9 }));
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners, vec![Foo { val: 8 }]);
+        assert_eq!(handoff.fill(Foo { val: 10 }), Err(Foo { val: 10 }));
+    }
+
+    #[test]
+    fn test_take_blocking_threads() {
+        let handoff = HandOff::empty();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || handoff_clone.take_blocking())
+            })
+            .collect();
+
+        std::thread::sleep(Duration::from_millis(20));
+        handoff.fill(Foo { val: 11 }).unwrap();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners, vec![Foo { val: 11 }]);
+    }
+
+    #[test]
+    fn test_take_timeout() {
+        let handoff = HandOff::empty();
+        let handoff_clone = handoff.clone();
+
+        assert_eq!(
+            handoff.clone().take_timeout(Duration::from_millis(5)),
+            Err(TakeError::TimedOut),
+        );
+
+        let consumer = std::thread::spawn(move || {
+            handoff_clone.take_timeout(Duration::from_secs(10))
+        });
+        handoff.fill(4).unwrap();
+
+        assert_eq!(consumer.join().unwrap(), Ok(4));
+        assert_eq!(
+            handoff.take_timeout(Duration::from_millis(5)),
+            Err(TakeError::Taken),
+        );
+    }
+
+    #[test]
+    fn test_handle_count_ignores_guards() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let handoff_clone = handoff.clone();
+
+        let lease = handoff.lease().unwrap();
+        let mapped = handoff_clone.clone().map(|foo| foo.val);
+        assert_eq!(handoff.handle_count(), 2);
+        assert_eq!(mapped.handle_count(), 1);
+
+        drop(lease);
+        drop(handoff_clone);
+        assert_eq!(handoff.handle_count(), 1);
+    }
+
+    #[test]
+    fn test_with_ref_reentry() {
+        let handoff = HandOff::new(Foo { val: 1 });
+
+        let reentered = handoff.with_ref(|_| (handoff.try_take(), format!("{handoff:?}")));
+        assert_eq!(
+            reentered,
+            Some((Err(TakeError::Claimed), String::from("HandOff { value: None }"))),
+        );
+
+        assert_eq!(handoff.with_ref(|_| handoff.cancel()), Some(None));
+        assert_eq!(handoff.try_take(), Err(TakeError::Cancelled { reason: None }));
+    }
+
+    #[test]
+    fn test_with_ref_does_not_block_takers() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let handoff_clone = handoff.clone();
+        let (inspecting, started) = std::sync::mpsc::channel();
+
+        let inspector = std::thread::spawn(move || {
+            handoff_clone.with_ref(|foo| {
+                inspecting.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(100));
+                foo.val
+            })
+        });
+        started.recv().unwrap();
+
+        let tried = Instant::now();
+        assert_eq!(handoff.try_take(), Err(TakeError::Claimed));
+        assert!(tried.elapsed() < Duration::from_millis(50));
+        assert_eq!(handoff.clone().take_blocking(), Some(Foo { val: 1 }));
+        assert_eq!(inspector.join().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_with_mut_panic_keeps_value() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let handoff_clone = handoff.clone();
+
+        let panicking = std::thread::spawn(move || {
+            handoff_clone.with_mut(|foo| {
+                foo.val = 2;
+                panic!("panic while inspecting");
+            })
+        });
+        assert!(panicking.join().is_err());
+
+        assert_eq!(handoff.with_ref(|foo| foo.val), Some(2));
+        assert_eq!(handoff.take(), Some(Foo { val: 2 }));
+    }
+
+    #[test]
+    fn test_take_if_threads() {
+        let handoff = HandOff::new(Foo { val: 6 });
+
+        let threads: Vec<_> = (0..8)
+            .map(|id| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || {
+                    handoff_clone.take_if(|foo| foo.val % 2 == id % 2).map(|foo| (id, foo))
+                })
+            })
+            .collect();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].0 % 2, 0);
+        assert_eq!(winners[0].1, Foo { val: 6 });
+    }
+
+    #[test]
+    fn test_into_inner_threads() {
+        let handoff = HandOff::new(Foo { val: 12 });
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || handoff_clone.into_inner())
+            })
+            .collect();
+
+        let winners: Vec<_> = std::iter::once(handoff.into_inner())
+            .chain(threads.into_iter().map(|thread| thread.join().unwrap()))
+            .flatten()
+            .collect();
+
+        assert_eq!(winners, vec![Foo { val: 12 }]);
+    }
+
+    #[test]
+    fn test_giver_reclaim() {
+        let giver = Giver::new(Foo { val: 2 });
+        let taker = giver.taker();
+
+        assert_eq!(giver.reclaim().ok(), Some(Foo { val: 2 }));
+        assert_eq!(taker.try_take(), Err(TakeError::Cancelled { reason: None }));
+
+        let giver = Giver::new(Foo { val: 3 });
+        assert_eq!(giver.taker().take(), Some(Foo { val: 3 }));
+        assert!(giver.reclaim().is_err());
+    }
+
+    #[test]
+    fn test_dropped_giver_cancels_empty_handoff() {
+        let (giver, taker) = HandOff::<Foo>::empty_pair();
+        let taker_clone = taker.clone();
+
+        let waiter = std::thread::spawn(move || taker_clone.take_blocking());
+        std::thread::sleep(Duration::from_millis(20));
+        drop(giver);
+
+        assert_eq!(waiter.join().unwrap(), None);
+        assert_eq!(taker.try_take(), Err(TakeError::Cancelled { reason: None }));
+
+        let (giver, taker) = HandOff::pair(Foo { val: 1 });
+        drop(giver);
+        assert_eq!(taker.take(), Some(Foo { val: 1 }));
+    }
+
+    #[test]
+    fn test_giver_wait_taken_abandoned() {
+        let (giver, taker) = HandOff::pair(Foo { val: 1 });
+        let taker_clone = taker.clone();
+        let lease = taker.lease().unwrap();
+        drop(taker);
+
+        let dropper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(taker_clone);
+            std::thread::sleep(Duration::from_millis(20));
+            drop(lease);
+        });
+
+        assert_eq!(giver.wait_taken_timeout(Duration::from_secs(10)), Err(TakeError::Abandoned));
+        dropper.join().unwrap();
+        assert_eq!(giver.reclaim().ok(), Some(Foo { val: 1 }));
+    }
+
+    #[test]
+    fn test_on_unclaimed_not_called_when_taken() {
+        let (requeue, orphans) = std::sync::mpsc::channel();
+        let handoff = HandOff::with_on_unclaimed(Foo { val: 5 }, move |foo| requeue.send(foo).unwrap());
+        let handoff_clone = handoff.clone();
+
+        let taker = std::thread::spawn(move || handoff_clone.take());
+        assert_eq!(taker.join().unwrap(), Some(Foo { val: 5 }));
+
+        drop(handoff);
+        assert!(orphans.recv().is_err());
+    }
+
+    #[test]
+    fn test_cancel_wakes_waiters() {
+        let handoff = HandOff::<Foo>::empty();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || handoff_clone.take_timeout(Duration::from_secs(10)))
+            })
+            .collect();
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(handoff.cancel_with("shutdown"), None);
+        assert_eq!(handoff.cancel(), None);
+
+        for thread in threads {
+            assert_eq!(
+                thread.join().unwrap(),
+                Err(TakeError::Cancelled { reason: Some("shutdown".into()) }),
+            );
+        }
+        assert_eq!(handoff.fill(Foo { val: 1 }), Err(Foo { val: 1 }));
+        assert_eq!(handoff.state(), HandOffState::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_after_take() {
+        let handoff = HandOff::new(Foo { val: 1 });
+
+        assert_eq!(handoff.clone().take(), Some(Foo { val: 1 }));
+        assert_eq!(handoff.cancel(), None);
+        assert_eq!(handoff.try_take(), Err(TakeError::Taken));
+    }
+
+    #[test]
+    fn test_pair_fill_threads() {
+        let (giver, taker) = HandOff::empty_pair();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let taker_clone = taker.clone();
+                std::thread::spawn(move || taker_clone.take_blocking())
+            })
+            .collect();
+
+        assert_eq!(giver.fill(Foo { val: 4 }), Ok(()));
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners, vec![Foo { val: 4 }]);
+        assert!(taker.is_taken());
+        assert!(giver.reclaim().is_err());
+    }
+
+    #[test]
+    fn test_wait_taken_cancelled() {
+        let (giver, taker) = HandOff::pair(Foo { val: 1 });
+        let taker_clone = taker.clone();
+
+        let waiter = std::thread::spawn(move || taker_clone.0.wait_taken());
+        std::thread::sleep(Duration::from_millis(20));
+
+        let cancelled = Err(TakeError::Cancelled { reason: Some("no capacity".into()) });
+        assert_eq!(giver.cancel_with("no capacity"), Some(Foo { val: 1 }));
+        assert_eq!(waiter.join().unwrap(), cancelled);
+        assert_eq!(giver.wait_taken_timeout(Duration::from_millis(1)), cancelled);
+    }
+
+    #[test]
+    fn test_lease_wakes_blocked_taker() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let handoff_clone = handoff.clone();
+
+        let mut lease = handoff.lease().unwrap();
+        let taker = std::thread::spawn(move || handoff_clone.take_blocking());
+        std::thread::sleep(Duration::from_millis(20));
+
+        lease.val = 2;
+        assert_eq!(handoff.state(), HandOffState::Claimed);
+        drop(lease);
+
+        assert_eq!(taker.join().unwrap(), Some(Foo { val: 2 }));
+    }
+
+    #[test]
+    fn test_cancel_while_leased() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let lease = handoff.lease().unwrap();
+
+        assert_eq!(handoff.cancel_with("closing"), None);
+        assert_eq!(handoff.try_take(), Err(TakeError::Cancelled { reason: Some("closing".into()) }));
+        assert_eq!(lease.val, 1);
+
+        drop(lease);
+        assert_eq!(handoff.state(), HandOffState::Cancelled);
+        assert!(handoff.lease().is_none());
+    }
+
+    #[test]
+    fn test_reusable_stale_handles() {
+        let slot = HandOff::reusable();
+
+        for round in 1..=3 {
+            let generation = slot.fill_generation(Foo { val: round }).unwrap();
+            assert_eq!(generation, round as u64);
+
+            let stale: Vec<_> = (1..generation)
+                .map(|old| {
+                    let slot_clone = slot.clone();
+                    std::thread::spawn(move || slot_clone.take_generation(old))
+                })
+                .collect();
+            for thread in stale {
+                assert_eq!(thread.join().unwrap(), Err(TakeError::Stale));
+            }
+
+            assert_eq!(slot.take_generation(generation), Ok(Foo { val: round }));
+            assert_eq!(slot.take_generation(generation), Err(TakeError::Taken));
+        }
+    }
+
+    #[test]
+    fn test_reusable_take_blocking_waits_for_refill() {
+        let slot = HandOff::reusable();
+        slot.fill(Foo { val: 1 }).unwrap();
+        assert_eq!(slot.try_take(), Ok(Foo { val: 1 }));
+
+        let slot_clone = slot.clone();
+        let consumer = std::thread::spawn(move || slot_clone.take_blocking());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(slot.fill_generation(Foo { val: 2 }), Ok(2));
+
+        assert_eq!(consumer.join().unwrap(), Some(Foo { val: 2 }));
+        assert_eq!(slot.take_timeout(Duration::from_millis(10)), Err(TakeError::TimedOut));
+    }
+
+    #[test]
+    fn test_cancel_reusable_after_take() {
+        let slot = HandOff::reusable();
+        slot.fill(Foo { val: 1 }).unwrap();
+        assert_eq!(slot.try_take(), Ok(Foo { val: 1 }));
+
+        let slot_clone = slot.clone();
+        let consumer = std::thread::spawn(move || {
+            let started = Instant::now();
+            (slot_clone.take_timeout(Duration::from_secs(2)), started.elapsed())
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(slot.cancel_with("shutdown"), None);
+
+        let (result, waited) = consumer.join().unwrap();
+        assert_eq!(result, Err(TakeError::Cancelled { reason: Some("shutdown".into()) }));
+        assert!(waited < Duration::from_secs(1));
+        assert_eq!(slot.state(), HandOffState::Cancelled);
+        assert_eq!(slot.fill(Foo { val: 2 }), Err(Foo { val: 2 }));
+    }
+
+    #[test]
+    fn test_reservation_abort_on_drop() {
+        let handoff = HandOff::new(Foo { val: 1 });
+        let handoff_clone = handoff.clone();
+
+        let reserver = std::thread::spawn(move || {
+            let reservation = handoff_clone.reserve().unwrap();
+            assert!(handoff_clone.reserve().is_none());
+            assert_eq!(reservation.val, 1);
+        });
+        reserver.join().unwrap();
+
+        assert_eq!(handoff.state(), HandOffState::Available);
+        assert_eq!(handoff.reserve().map(Reservation::commit), Some(Foo { val: 1 }));
+    }
+
+    #[test]
+    fn test_lazy_runs_once_in_winner() {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runs_clone = runs.clone();
+        let handoff = HandOff::lazy(move || {
+            runs_clone.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            std::thread::current().id()
+        });
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || {
+                    handoff_clone.take().map(|id| (id, std::thread::current().id()))
+                })
+            })
+            .collect();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].0, winners[0].1);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_lazy_not_run_when_unclaimed() {
+        let handoff = HandOff::<Foo>::lazy(|| panic!("must not run"));
+
+        assert_eq!(handoff.with_ref(|foo| foo.val), None);
+        assert!(handoff.lease().is_none());
+        assert_eq!(handoff.cancel(), None);
+        assert_eq!(handoff.try_take(), Err(TakeError::Cancelled { reason: None }));
+    }
+
+    #[test]
+    fn test_try_lazy_poison_shares_error() {
+        let handoff = HandOff::<Foo>::try_lazy(LazyPolicy::Poison, || {
+            std::thread::sleep(Duration::from_millis(20));
+            Err(std::io::Error::other("unreachable"))
+        });
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || handoff_clone.take_timeout(Duration::from_secs(5)))
+            })
+            .collect();
+
+        let errors: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap().unwrap_err())
+            .collect();
+
+        assert!(matches!(errors[0], TakeError::InitFailed { .. }));
+        assert!(errors.iter().all(|error| *error == errors[0]));
+        assert_eq!(handoff.try_take(), Err(errors[0].clone()));
+    }
+
+    #[test]
+    fn test_try_lazy_retry() {
+        let mut attempts = 0;
+        let handoff = HandOff::try_lazy(LazyPolicy::Retry, move || {
+            attempts += 1;
+            match attempts {
+                1 => Err(std::io::Error::other("busy")),
+                _ => Ok(Foo { val: attempts }),
+            }
+        });
+
+        assert!(matches!(handoff.try_take(), Err(TakeError::InitFailed { .. })));
+        assert_eq!(handoff.state(), HandOffState::Available);
+        assert_eq!(handoff.try_take(), Ok(Foo { val: 2 }));
+    }
+
+    #[test]
+    fn test_lazy_async_dropped_winner_hands_back_init() {
+        use std::task::{ Context, Poll, Waker };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let handoff = HandOff::lazy_async(async move {
+            std::future::poll_fn(move |_| match receiver.try_recv() {
+                Ok(val) => Poll::Ready(val),
+                Err(_) => Poll::Pending,
+            }).await
+        });
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut future = Box::pin(handoff.clone().take_async());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(handoff.try_take(), Err(TakeError::Claimed));
+        drop(future);
+
+        assert_eq!(handoff.state(), HandOffState::Available);
+        sender.send(3).unwrap();
+        assert_eq!(handoff.take_blocking(), Some(3));
+    }
+
+    #[test]
+    fn test_lazy_async_try_take_does_not_wait() {
+        let handoff = HandOff::<Foo>::lazy_async(std::future::pending());
+
+        assert_eq!(handoff.try_take(), Err(TakeError::Lazy));
+        assert_eq!(handoff.take_if(|_| true), None);
+        assert_eq!(HandOff::take_any(std::slice::from_ref(&handoff)), None);
+        assert_eq!(handoff.state(), HandOffState::Available);
+    }
+
+    #[test]
+    fn test_cancel_during_async_init() {
+        use std::task::{ Context, Poll, Waker };
+
+        let handoff = HandOff::<Foo>::lazy_async(std::future::pending());
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut future = Box::pin(handoff.clone().take_async());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(handoff.cancel_with("stop"), None);
+        assert_eq!(handoff.state(), HandOffState::Cancelled);
+
+        drop(future);
+        assert_eq!(handoff.state(), HandOffState::Cancelled);
+        assert_eq!(
+            handoff.try_take(),
+            Err(TakeError::Cancelled { reason: Some("stop".into()) }),
+        );
+    }
+
+    #[test]
+    fn test_cancel_during_retried_init() {
+        let (started_sender, started) = std::sync::mpsc::channel();
+        let (resume, resumed) = std::sync::mpsc::channel();
+        let mut failed = false;
+        let handoff = HandOff::try_lazy(LazyPolicy::Retry, move || {
+            if failed {
+                return Ok(Foo { val: 2 });
+            }
+            failed = true;
+            started_sender.send(()).unwrap();
+            resumed.recv().unwrap();
+            Err(std::io::Error::other("not yet"))
+        });
+        let handoff_clone = handoff.clone();
+
+        let taker = std::thread::spawn(move || handoff_clone.try_take());
+        started.recv().unwrap();
+        assert_eq!(handoff.cancel_with("stop"), None);
+        resume.send(()).unwrap();
+
+        assert!(matches!(taker.join().unwrap(), Err(TakeError::InitFailed { .. })));
+        assert_eq!(
+            handoff.try_take(),
+            Err(TakeError::Cancelled { reason: Some("stop".into()) }),
+        );
+    }
+
+    #[test]
+    fn test_map_single_winner() {
+        let handoff = HandOff::new(Foo { val: 4 });
+        let mapped = handoff.clone().map(|foo| foo.val * 2);
+
+        let original_thread = std::thread::spawn(move || handoff.take().map(|foo| foo.val));
+        let mapped_thread = std::thread::spawn(move || mapped.take());
+
+        let winners: Vec<_> = [original_thread, mapped_thread]
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert!(winners == [4] || winners == [8]);
+    }
+
+    #[test]
+    fn test_map_follows_source() {
+        let handoff = HandOff::new(Foo { val: 4 });
+        let mapped = handoff.clone().map(|foo| foo.val);
+
+        assert_eq!(mapped.state(), HandOffState::Available);
+        assert_eq!(handoff.take(), Some(Foo { val: 4 }));
+        assert_eq!(mapped.state(), HandOffState::Taken);
+        assert_eq!(mapped.try_take(), Err(TakeError::Taken));
+        assert_eq!(mapped.wait_taken(), Ok(()));
+    }
+
+    #[test]
+    fn test_map_reclaim_leaves_source() {
+        let handoff = HandOff::new(Foo { val: 4 });
+        let mapped = handoff.clone().map(|foo| foo.val);
+
+        let mapped = mapped.reclaim().unwrap_err();
+        assert_eq!(mapped.into_inner(), None);
+        assert_eq!(HandOff::lazy(|| Foo { val: 1 }).reclaim().ok(), None);
+        assert_eq!(handoff.take(), Some(Foo { val: 4 }));
+    }
+
+    #[test]
+    fn test_map_blocks_until_source_filled() {
+        let handoff = HandOff::empty();
+        let mapped = handoff.clone().map(|foo: Foo| foo.val + 1);
+
+        let taker = std::thread::spawn(move || mapped.take_blocking());
+        std::thread::sleep(Duration::from_millis(20));
+        handoff.fill(Foo { val: 1 }).unwrap();
+
+        assert_eq!(taker.join().unwrap(), Some(2));
+        assert!(handoff.is_taken());
+    }
+
+    #[test]
+    fn test_map_pending_take_does_not_wake_itself() {
+        use std::sync::atomic::{ AtomicUsize, Ordering };
+        use std::task::{ Context, Poll, Wake, Waker };
+
+        struct CountingWaker(AtomicUsize);
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let handoff = HandOff::empty();
+        let mapped = handoff.clone().map(|foo: Foo| foo.val + 1);
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = Box::pin(mapped.take_async());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 0);
+
+        let lease = handoff.fill(Foo { val: 1 }).ok().and_then(|()| handoff.lease()).unwrap();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+
+        drop(lease);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some(2)));
+    }
+
+    #[test]
+    fn test_unwind_safe() {
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+
+        assert_unwind_safe::<HandOff<std::cell::Cell<i32>>>();
+        assert_unwind_safe::<Giver<std::cell::Cell<i32>>>();
+        assert_unwind_safe::<Taker<std::cell::Cell<i32>>>();
+    }
+
+    #[test]
+    fn test_guards_send_sync() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+
+        assert_send::<Lease<std::cell::Cell<i32>>>();
+        assert_send::<Reservation<std::cell::Cell<i32>>>();
+        assert_sync::<Lease<i32>>();
+        assert_sync::<Reservation<i32>>();
+    }
+
+    #[test]
+    #[cfg(not(feature = "diagnostics"))]
+    fn test_debug() {
+        let handoff = HandOff::new(5);
+
+        assert_eq!(format!("{handoff:?}"), "HandOff { value: Some(5) }");
+        handoff.clone().take();
+        assert_eq!(format!("{handoff:?}"), "HandOff { value: None }");
+    }
+
+    #[test]
+    #[cfg(feature = "diagnostics")]
+    fn test_taken_by() {
+        let handoff = HandOff::new(Foo { val: 5 });
+        let handoff_clone = handoff.clone();
+        assert_eq!(handoff.taken_by(), None);
+
+        let winner = std::thread::spawn(move || {
+            handoff_clone.take();
+            std::thread::current().id()
+        }).join().unwrap();
+
+        let taken_by = handoff.taken_by().unwrap();
+        assert_eq!(taken_by.thread_id(), winner);
+        assert_eq!(handoff.try_take(), Err(TakeError::Taken));
+        assert_eq!(handoff.taken_by(), Some(taken_by));
+        assert!(format!("{handoff:?}").starts_with("HandOff { value: None, taken_by: TakenBy {"));
     }
 }
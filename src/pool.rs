@@ -0,0 +1,236 @@
+use std::cell::UnsafeCell;
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Arc;
+
+/// A syncing type for handing out many objects, each to exactly one taker.
+///
+/// A `HandOffPool` is created with a batch of values and can be cloned and
+/// sent between threads like a [`HandOff`](crate::HandOff). Every value is
+/// received by exactly one taker, so N workers can each claim a distinct unit
+/// of work from one shared allocation instead of a `Vec` of handoffs.
+///
+/// Values are handed out in the order they were given, and claiming one is a
+/// single atomic operation.
+///
+/// # Example
+/// ```
+/// use takeit::HandOffPool;
+///
+/// let jobs = HandOffPool::new(0..100);
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let jobs = jobs.clone();
+///         std::thread::spawn(move || {
+///             let mut done = 0;
+///             while let Some(_job) = jobs.take_one() {
+///                 done += 1;
+///             }
+///             done
+///         })
+///     })
+///     .collect();
+///
+/// let done: usize = workers.into_iter().map(|worker| worker.join().unwrap()).sum();
+/// assert_eq!(done, 100);
+/// ```
+pub struct HandOffPool<T>(Arc<PoolInner<T>>);
+
+/// The values shared between all the clones of a `HandOffPool`.
+///
+/// Values at indices below `next` were moved out; the rest are initialized.
+/// Whoever advances `next` past an index owns the value at that index.
+struct PoolInner<T> {
+    next: AtomicUsize,
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+// SAFETY: Every value is only accessed by the single thread that advanced
+// `next` past it, or by the drop of the last handle.
+unsafe impl<T: Send> Send for PoolInner<T> {}
+unsafe impl<T: Send> Sync for PoolInner<T> {}
+
+impl<T> HandOffPool<T> {
+    /// Creates a new HandOffPool object holding every value of `values`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOffPool;
+    ///
+    /// let pool = HandOffPool::new(vec!["a", "b", "c"]);
+    /// ```
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let slots = values
+            .into_iter()
+            .map(|val| UnsafeCell::new(MaybeUninit::new(val)))
+            .collect();
+
+        Self(Arc::new(PoolInner {
+            next: AtomicUsize::new(0),
+            slots,
+        }))
+    }
+
+    /// Claims `count` values, or as many as are left, returning their indices.
+    fn claim(&self, count: usize) -> Range<usize> {
+        let len = self.0.slots.len();
+        match self.0.next.fetch_update(Ordering::AcqRel, Ordering::Acquire, |next| {
+            (next < len && count > 0).then(|| next + count.min(len - next))
+        }) {
+            Ok(start) => start..start + count.min(len - start),
+            Err(_) => len..len,
+        }
+    }
+
+    /// Moves one value out of the pool, giving every caller a distinct value.
+    ///
+    /// # Errors
+    /// If every value was already taken, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOffPool;
+    ///
+    /// let pool = HandOffPool::new([1, 2]);
+    /// let pool_clone = pool.clone();
+    ///
+    /// assert_eq!(pool.take_one(), Some(1));
+    /// assert_eq!(pool_clone.take_one(), Some(2));
+    /// assert_eq!(pool.take_one(), None);
+    /// ```
+    pub fn take_one(&self) -> Option<T> {
+        self.claim(1).next().map(|index| {
+            // SAFETY: We advanced `next` past `index`, so the value is
+            // initialized and nobody else can access it.
+            unsafe { (*self.0.slots[index].get()).assume_init_read() }
+        })
+    }
+
+    /// Moves up to `count` values out of the pool at once.
+    ///
+    /// The values are claimed in a single atomic operation, so they are
+    /// consecutive and no other taker receives any of them. Fewer than `count`
+    /// values are returned if the pool runs out.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOffPool;
+    ///
+    /// let pool = HandOffPool::new(1..=5);
+    ///
+    /// assert_eq!(pool.take_n(3), vec![1, 2, 3]);
+    /// assert_eq!(pool.take_n(3), vec![4, 5]);
+    /// assert_eq!(pool.take_n(3), Vec::<i32>::new());
+    /// ```
+    pub fn take_n(&self, count: usize) -> Vec<T> {
+        self.claim(count)
+            .map(|index| {
+                // SAFETY: We advanced `next` past `index`, so the value is
+                // initialized and nobody else can access it.
+                unsafe { (*self.0.slots[index].get()).assume_init_read() }
+            })
+            .collect()
+    }
+
+    /// Returns the number of values that were not taken yet.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOffPool;
+    ///
+    /// let pool = HandOffPool::new(["a", "b"]);
+    /// pool.take_one();
+    ///
+    /// assert_eq!(pool.remaining(), 1);
+    /// ```
+    pub fn remaining(&self) -> usize {
+        let len = self.0.slots.len();
+        len - self.0.next.load(Ordering::Acquire).min(len)
+    }
+}
+
+impl<T> FromIterator<T> for HandOffPool<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        Self::new(values)
+    }
+}
+
+impl<T> Clone for HandOffPool<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for HandOffPool<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("HandOffPool")
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+impl<T> Drop for PoolInner<T> {
+    fn drop(&mut self) {
+        let next = (*self.next.get_mut()).min(self.slots.len());
+        for slot in &mut self.slots[next..] {
+            // SAFETY: Values at or after `next` were never moved out.
+            unsafe { slot.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_take_once_threads() {
+        let pool: HandOffPool<_> = (0..1000).map(|val| val.to_string()).collect();
+
+        let threads: Vec<_> = (0..8)
+            .map(|id| {
+                let pool_clone = pool.clone();
+                std::thread::spawn(move || {
+                    let mut taken = Vec::new();
+                    loop {
+                        let batch = if id % 2 == 0 {
+                            pool_clone.take_one().into_iter().collect()
+                        } else {
+                            pool_clone.take_n(7)
+                        };
+                        if batch.is_empty() {
+                            return taken;
+                        }
+                        taken.extend(batch);
+                    }
+                })
+            })
+            .collect();
+
+        let taken: Vec<_> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect();
+        let unique: HashSet<_> = taken.iter().cloned().collect();
+
+        assert_eq!(taken.len(), 1000);
+        assert_eq!(unique.len(), 1000);
+        assert_eq!(pool.remaining(), 0);
+    }
+
+    #[test]
+    fn test_drop_remaining() {
+        let counter = Arc::new(());
+        let pool = HandOffPool::new((0..4).map(|_| counter.clone()));
+
+        drop(pool.take_n(3));
+        assert_eq!(Arc::strong_count(&counter), 2);
+
+        drop(pool);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+}
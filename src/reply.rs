@@ -0,0 +1,221 @@
+use std::error::Error;
+use std::fmt::{ Debug, Display, Formatter, Result as FmtResult };
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ Context, Poll };
+use std::time::{ Duration, Instant };
+
+use crate::inner::Inner;
+use crate::TakeError;
+
+/// The reason a reply could not be received by a [`ReplyReceiver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReplyError {
+    /// The [`Reply`] was dropped without answering, either by the taker of the
+    /// value or because nobody took the value at all.
+    Dropped,
+    /// Waiting for the reply took longer than the timeout.
+    TimedOut,
+}
+
+impl Display for ReplyError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            ReplyError::Dropped => write!(fmt, "the reply was dropped without answering"),
+            ReplyError::TimedOut => write!(fmt, "timed out waiting for the reply"),
+        }
+    }
+}
+
+impl Error for ReplyError {}
+
+impl From<TakeError> for ReplyError {
+    fn from(err: TakeError) -> Self {
+        match err {
+            TakeError::TimedOut => ReplyError::TimedOut,
+            _ => ReplyError::Dropped,
+        }
+    }
+}
+
+/// A one-shot handle for answering the originator of a handoff.
+///
+/// Created by [`HandOff::with_reply`](crate::HandOff::with_reply) and handed to
+/// whoever takes the value. If it is dropped without calling [`Reply::send`],
+/// the [`ReplyReceiver`] fails with [`ReplyError::Dropped`].
+pub struct Reply<R>(Option<Arc<Inner<R>>>);
+
+impl<R> Reply<R> {
+    pub(crate) fn new() -> (Self, ReplyReceiver<R>) {
+        let inner = Arc::new(Inner::empty());
+        (Self(Some(inner.clone())), ReplyReceiver(inner))
+    }
+
+    /// Sends the reply to the originator.
+    ///
+    /// # Errors
+    /// If the [`ReplyReceiver`] was already dropped, `val` is handed back in
+    /// `Err`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let (handoff, receiver) = HandOff::with_reply::<usize>("job");
+    /// let (job, reply) = handoff.take().unwrap();
+    ///
+    /// assert_eq!(reply.send(job.len()), Ok(()));
+    /// assert_eq!(receiver.recv(), Ok(3));
+    /// ```
+    pub fn send(mut self, val: R) -> Result<(), R> {
+        let inner = self.0.take().expect("reply already sent");
+        if Arc::strong_count(&inner) == 1 {
+            return Err(val);
+        }
+
+        inner.fill(val).map(|_| ())
+    }
+}
+
+impl<R> Drop for Reply<R> {
+    fn drop(&mut self) {
+        if let Some(inner) = self.0.take() {
+            inner.cancel(None);
+        }
+    }
+}
+
+impl<R> Debug for Reply<R> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("Reply").finish_non_exhaustive()
+    }
+}
+
+/// The originator's side of a reply created by
+/// [`HandOff::with_reply`](crate::HandOff::with_reply).
+pub struct ReplyReceiver<R>(Arc<Inner<R>>);
+
+impl<R> ReplyReceiver<R> {
+    /// Blocks the current thread until the taker sends a reply.
+    ///
+    /// # Errors
+    /// Returns [`ReplyError::Dropped`] if the [`Reply`] was dropped without
+    /// answering.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::{ HandOff, ReplyError };
+    ///
+    /// let (handoff, receiver) = HandOff::with_reply::<i32>("job");
+    /// drop(handoff);
+    ///
+    /// assert_eq!(receiver.recv(), Err(ReplyError::Dropped));
+    /// ```
+    pub fn recv(self) -> Result<R, ReplyError> {
+        Ok(self.0.take_blocking(None)?)
+    }
+
+    /// Blocks the current thread for at most `timeout` until the taker sends a
+    /// reply.
+    ///
+    /// # Errors
+    /// Returns [`ReplyError::TimedOut`] if no reply arrived in time, or
+    /// [`ReplyError::Dropped`] if the [`Reply`] was dropped without answering.
+    pub fn recv_timeout(self, timeout: Duration) -> Result<R, ReplyError> {
+        Ok(self.0.take_blocking(Some(Instant::now() + timeout))?)
+    }
+
+    /// Returns a future that resolves to the reply once the taker sends it.
+    ///
+    /// # Errors
+    /// The future resolves to [`ReplyError::Dropped`] if the [`Reply`] was
+    /// dropped without answering.
+    pub fn recv_async(self) -> ReplyFuture<R> {
+        ReplyFuture {
+            receiver: self,
+            key: None,
+        }
+    }
+}
+
+impl<R> Debug for ReplyReceiver<R> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("ReplyReceiver").finish_non_exhaustive()
+    }
+}
+
+/// A future that resolves to the reply sent to a [`ReplyReceiver`].
+///
+/// Created by [`ReplyReceiver::recv_async`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ReplyFuture<R> {
+    receiver: ReplyReceiver<R>,
+    key: Option<usize>,
+}
+
+impl<R> Future for ReplyFuture<R> {
+    type Output = Result<R, ReplyError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<R, ReplyError>> {
+        let this = self.get_mut();
+        this.receiver.0.poll_take(&mut this.key, cx).map_err(ReplyError::from)
+    }
+}
+
+impl<R> Drop for ReplyFuture<R> {
+    fn drop(&mut self) {
+        self.receiver.0.unregister(&mut self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ HandOff, ReplyError };
+    use std::time::Duration;
+
+    #[test]
+    fn test_reply_threads() {
+        let (handoff, receiver) = HandOff::with_reply::<String>(21);
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let handoff_clone = handoff.clone();
+                std::thread::spawn(move || {
+                    if let Some((val, reply)) = handoff_clone.take() {
+                        reply.send(format!("{}", val * 2)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(handoff);
+
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(String::from("42")));
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_reply_dropped_by_taker() {
+        let (handoff, receiver) = HandOff::with_reply::<i32>(1);
+
+        let worker = std::thread::spawn(move || {
+            let (_val, reply) = handoff.take().unwrap();
+            drop(reply);
+        });
+
+        assert_eq!(receiver.recv(), Err(ReplyError::Dropped));
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_send_after_receiver_dropped() {
+        let (handoff, receiver) = HandOff::with_reply::<i32>(1);
+        drop(receiver);
+
+        let (_val, reply) = handoff.take().unwrap();
+        assert_eq!(reply.send(5), Err(5));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::ops::Deref;
+
+use crate::Lease;
+
+/// A claim on the value of a `HandOff` that is either committed or aborted.
+///
+/// Created by [`HandOff::reserve`](crate::HandOff::reserve). While the
+/// reservation is alive, other handles see the value as claimed and cannot
+/// take it, but no lock is held. The value is taken with
+/// [`Reservation::commit`], or made available again with
+/// [`Reservation::abort`] or by dropping the reservation.
+///
+/// # Example
+/// ```
+/// use takeit::HandOff;
+///
+/// let job = HandOff::new(String::from("resize image"));
+///
+/// let reservation = job.reserve().unwrap();
+/// let quota_left = false;
+/// if quota_left {
+///     let _job = reservation.commit();
+/// } else {
+///     reservation.abort();
+/// }
+///
+/// assert!(job.is_available());
+/// ```
+///
+/// It derefs to the reserved value, so like a [`Lease`] it can only be shared
+/// between threads if `T` is `Sync`:
+///
+/// ```compile_fail
+/// use std::cell::Cell;
+/// use takeit::Reservation;
+///
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<Reservation<Cell<u64>>>();
+/// ```
+pub struct Reservation<T>(Lease<T>);
+
+impl<T> Reservation<T> {
+    pub(crate) fn new(lease: Lease<T>) -> Self {
+        Self(lease)
+    }
+
+    /// Takes the reserved value, marking the value of the `HandOff` as taken.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let reservation = handoff.reserve().unwrap();
+    ///
+    /// assert_eq!(reservation.commit(), 10);
+    /// assert!(handoff.is_taken());
+    /// ```
+    pub fn commit(self) -> T {
+        self.0.consume()
+    }
+
+    /// Gives up the reservation, making the value available to the other
+    /// handles again and waking the ones waiting for it.
+    ///
+    /// This is the same as dropping the reservation.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let handoff = HandOff::new(10);
+    /// let reservation = handoff.reserve().unwrap();
+    ///
+    /// reservation.abort();
+    /// assert_eq!(handoff.take(), Some(10));
+    /// ```
+    pub fn abort(self) {}
+}
+
+impl<T> Deref for Reservation<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Debug> Debug for Reservation<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.debug_struct("Reservation").field("value", &**self).finish()
+    }
+}
@@ -0,0 +1,274 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ Context, Poll };
+use std::thread;
+
+use crate::lazy::{ AsyncInit, InitMode, Took };
+use crate::waiters::thread_waker;
+use crate::{ HandOff, TakeError };
+
+impl<T> HandOff<T> {
+    /// Takes the value of the first `HandOff` in `handoffs` that has one,
+    /// returning its index along with the value.
+    ///
+    /// At most one value is taken. The handoffs are tried in order, so earlier
+    /// handoffs take priority over later ones.
+    ///
+    /// # Errors
+    /// If none of the handoffs holds a value right now, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let high = HandOff::empty();
+    /// let low = HandOff::new("low");
+    /// let slots = [high.clone(), low.clone()];
+    ///
+    /// assert_eq!(HandOff::take_any(&slots), Some((1, "low")));
+    /// assert_eq!(HandOff::take_any(&slots), None);
+    /// ```
+    pub fn take_any(handoffs: &[HandOff<T>]) -> Option<(usize, T)> {
+        let (index, took) = poll_any(handoffs, InitMode::Try).flatten()?;
+        Some((index, handoffs[index].0.finish_blocking(took)))
+    }
+
+    /// Like [`HandOff::take_any`], but blocks the current thread until one of
+    /// the handoffs holds a value.
+    ///
+    /// # Errors
+    /// If every handoff was taken or cancelled, so that none of them can
+    /// become available anymore, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let slots = [HandOff::empty(), HandOff::empty()];
+    /// let producer_slot = slots[1].clone();
+    ///
+    /// let producer = std::thread::spawn(move || producer_slot.fill(5).unwrap());
+    ///
+    /// assert_eq!(HandOff::take_any_blocking(&slots), Some((1, 5)));
+    /// producer.join().unwrap();
+    /// ```
+    pub fn take_any_blocking(handoffs: &[HandOff<T>]) -> Option<(usize, T)> {
+        let ready = poll_any(handoffs, InitMode::Block).unwrap_or_else(|| {
+            let waker = thread_waker();
+            let mut keys = vec![None; handoffs.len()];
+
+            let ready = loop {
+                for (handoff, key) in handoffs.iter().zip(&mut keys) {
+                    handoff.0.register(key, &waker);
+                }
+                if let Some(ready) = poll_any(handoffs, InitMode::Block) {
+                    break ready;
+                }
+
+                thread::park();
+            };
+
+            unregister_all(handoffs, &mut keys);
+            ready
+        });
+
+        let (index, took) = ready?;
+        Some((index, handoffs[index].0.finish_blocking(took)))
+    }
+
+    /// Returns a future that resolves to the value of the first handoff in
+    /// `handoffs` that holds one, along with its index. See
+    /// [`HandOff::take_any_blocking`].
+    ///
+    /// Dropping the future before it resolves leaves every value in place. If
+    /// it was awaiting the initializer of a [`HandOff::lazy_async`], the
+    /// initializer is handed back unfinished like with [`HandOff::take_async`].
+    ///
+    /// # Errors
+    /// If every handoff was taken or cancelled, the future resolves to `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::future::Future;
+    /// use std::task::{ Context, Poll, Waker };
+    /// use takeit::HandOff;
+    ///
+    /// let slots = [HandOff::empty(), HandOff::empty()];
+    /// let mut future = std::pin::pin!(HandOff::take_any_async(&slots));
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+    /// slots[0].fill(3).unwrap();
+    /// assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some((0, 3))));
+    /// ```
+    pub fn take_any_async(handoffs: &[HandOff<T>]) -> TakeAnyFuture<'_, T> {
+        TakeAnyFuture {
+            handoffs,
+            keys: vec![None; handoffs.len()],
+            index: 0,
+            init: None,
+        }
+    }
+}
+
+/// Takes the first available value, or returns `Some(None)` once none of the
+/// handoffs can become available anymore. Returns `None` if the caller should
+/// wait.
+///
+/// Unless `mode` is `InitMode::Try`, the winner may get an async initializer
+/// that the caller must finish or hand back. Values that are ready win over
+/// initializers that still have to run, whatever their order.
+fn poll_any<T>(handoffs: &[HandOff<T>], mode: InitMode) -> Option<Option<(usize, Took<T>)>> {
+    let mut pending = false;
+    let mut lazy = false;
+    for (index, handoff) in handoffs.iter().enumerate() {
+        match handoff.0.take() {
+            Ok(val) => return Some(Some((index, Took::Value(val)))),
+            Err(TakeError::Lazy) if mode != InitMode::Try => lazy = true,
+            Err(err) if handoff.0.should_wait(&err) => pending = true,
+            Err(_) => {},
+        }
+    }
+
+    if lazy {
+        for (index, handoff) in handoffs.iter().enumerate() {
+            match handoff.0.take_or_init(mode) {
+                Ok(took) => return Some(Some((index, took))),
+                Err(err) if handoff.0.should_wait(&err) => pending = true,
+                Err(_) => {},
+            }
+        }
+    }
+
+    (!pending).then_some(None)
+}
+
+fn unregister_all<T>(handoffs: &[HandOff<T>], keys: &mut [Option<usize>]) {
+    for (handoff, key) in handoffs.iter().zip(keys) {
+        handoff.0.unregister(key);
+    }
+}
+
+/// A future that resolves to the value of the first of several handoffs that
+/// holds one.
+///
+/// Created by [`HandOff::take_any_async`].
+#[must_use = "futures do nothing unless polled"]
+pub struct TakeAnyFuture<'a, T> {
+    handoffs: &'a [HandOff<T>],
+    keys: Vec<Option<usize>>,
+    /// The handoff whose async initializer is held in `init`.
+    index: usize,
+    init: Option<AsyncInit<T>>,
+}
+
+impl<T> TakeAnyFuture<'_, T> {
+    /// Takes the first available value, or the initializer of the first lazy
+    /// handoff, registering the task of `cx` on every handoff if it has to
+    /// wait.
+    fn poll_take(&mut self, cx: &mut Context<'_>) -> Poll<Option<(usize, Took<T>)>> {
+        let ready = poll_any(self.handoffs, InitMode::Async).or_else(|| {
+            for (handoff, key) in self.handoffs.iter().zip(&mut self.keys) {
+                handoff.0.register(key, cx.waker());
+            }
+            poll_any(self.handoffs, InitMode::Async)
+        });
+
+        match ready {
+            Some(ready) => {
+                unregister_all(self.handoffs, &mut self.keys);
+                Poll::Ready(ready)
+            },
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Future for TakeAnyFuture<'_, T> {
+    type Output = Option<(usize, T)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<(usize, T)>> {
+        let this = self.get_mut();
+        if this.init.is_none() {
+            match this.poll_take(cx) {
+                Poll::Ready(Some((index, Took::Value(val)))) => {
+                    return Poll::Ready(Some((index, val)));
+                },
+                Poll::Ready(Some((index, Took::Init(init)))) => {
+                    this.index = index;
+                    this.init = Some(init);
+                },
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let index = this.index;
+        this.handoffs[index].0.poll_init(&mut this.init, cx).map(|val| Some((index, val)))
+    }
+}
+
+impl<T> Drop for TakeAnyFuture<'_, T> {
+    fn drop(&mut self) {
+        unregister_all(self.handoffs, &mut self.keys);
+        if let Some(init) = self.init.take() {
+            self.handoffs[self.index].0.abort_init(init);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_take_any_blocking_takes_one() {
+        let slots: Vec<HandOff<i32>> = (0..3).map(|_| HandOff::empty()).collect();
+        let producer_slots = slots.clone();
+
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            producer_slots[2].fill(2).unwrap();
+        });
+
+        assert_eq!(HandOff::take_any_blocking(&slots), Some((2, 2)));
+        producer.join().unwrap();
+
+        slots[1].fill(1).unwrap();
+        slots[0].fill(0).unwrap();
+        assert_eq!(HandOff::take_any_blocking(&slots), Some((0, 0)));
+        assert!(slots[1].is_available());
+    }
+
+    #[test]
+    fn test_take_any_blocking_all_gone() {
+        let slots = [HandOff::<i32>::empty(), HandOff::empty()];
+        let canceller_slots = slots.clone();
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            for slot in &canceller_slots {
+                slot.cancel();
+            }
+        });
+
+        assert_eq!(HandOff::take_any_blocking(&slots), None);
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn test_take_any_async_prefers_ready_values() {
+        let slots = [HandOff::lazy_async(std::future::pending()), HandOff::new(1)];
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+
+        let mut future = Box::pin(HandOff::take_any_async(&slots));
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(Some((1, 1))));
+
+        let mut future = Box::pin(HandOff::take_any_async(&slots));
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(slots[0].try_take(), Err(TakeError::Claimed));
+        drop(future);
+        assert_eq!(slots[0].try_take(), Err(TakeError::Lazy));
+    }
+}
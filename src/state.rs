@@ -0,0 +1,24 @@
+/// A snapshot of where a `HandOff` is in its lifecycle.
+///
+/// Returned by [`HandOff::state`](crate::HandOff::state). Other handles may
+/// change the state right after it was read, so it is only a hint unless no
+/// other handle can act on the `HandOff` anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HandOffState {
+    /// The `HandOff` holds a value that can be taken.
+    Available,
+    /// The value was taken by some handle.
+    Taken,
+    /// The `HandOff` was created empty and has not been filled yet.
+    Empty,
+    /// The `HandOff` was cancelled before its value was taken.
+    Cancelled,
+    /// The value is temporarily claimed by a [`Lease`](crate::Lease) or a
+    /// [`Reservation`](crate::Reservation), or is being produced by the
+    /// initializer of a lazy `HandOff`, and may become available again.
+    Claimed,
+    /// The initializer of a lazy `HandOff` failed and poisoned it, see
+    /// [`LazyPolicy::Poison`](crate::LazyPolicy::Poison).
+    Poisoned,
+}
@@ -0,0 +1,130 @@
+use std::fmt::{ Debug, Formatter, Result as FmtResult };
+use std::time::Duration;
+
+use crate::{ HandOff, HandOffState, Lease, Reservation, TakeError, TakeFuture };
+#[cfg(feature = "diagnostics")]
+use crate::TakenBy;
+
+/// The consuming side of a handoff created with [`HandOff::pair`].
+///
+/// A `Taker` can be cloned and sent between threads like a [`HandOff`], and
+/// the first clone to take the value receives it. Unlike a `HandOff`, it cannot
+/// fill or cancel the handoff: only the matching [`Giver`](crate::Giver) can.
+///
+/// # Example
+/// ```
+/// use takeit::HandOff;
+///
+/// let (giver, taker) = HandOff::pair(String::from("job"));
+/// let taker_clone = taker.clone();
+///
+/// let worker = std::thread::spawn(move || taker_clone.take());
+///
+/// assert_eq!(worker.join().unwrap(), Some(String::from("job")));
+/// assert_eq!(taker.take(), None);
+/// assert!(giver.reclaim().is_err());
+/// ```
+pub struct Taker<T>(pub(crate) HandOff<T>);
+
+impl<T> Taker<T> {
+    /// Returns the value by moving it. See [`HandOff::take`].
+    pub fn take(self) -> Option<T> {
+        self.0.take()
+    }
+
+    /// Attempts to move the value out without consuming the handle. See
+    /// [`HandOff::try_take`].
+    pub fn try_take(&self) -> Result<T, TakeError> {
+        self.0.try_take()
+    }
+
+    /// Moves the value out only if `pred` returns `true` for it. See
+    /// [`HandOff::take_if`].
+    pub fn take_if(&self, pred: impl FnOnce(&T) -> bool) -> Option<T> {
+        self.0.take_if(pred)
+    }
+
+    /// Returns the value, blocking until the handoff is filled. See
+    /// [`HandOff::take_blocking`].
+    pub fn take_blocking(self) -> Option<T> {
+        self.0.take_blocking()
+    }
+
+    /// Returns the value, blocking for at most `timeout` until the handoff is
+    /// filled. See [`HandOff::take_timeout`].
+    pub fn take_timeout(self, timeout: Duration) -> Result<T, TakeError> {
+        self.0.take_timeout(timeout)
+    }
+
+    /// Returns a future that resolves to the value once the handoff is filled.
+    /// See [`HandOff::take_async`].
+    pub fn take_async(self) -> TakeFuture<T> {
+        self.0.take_async()
+    }
+
+    /// Temporarily takes the value, putting it back when the lease is dropped.
+    /// See [`HandOff::lease`].
+    pub fn lease(&self) -> Option<Lease<T>> {
+        self.0.lease()
+    }
+
+    /// Like [`Taker::lease`], but blocks while the handoff is empty or leased.
+    /// See [`HandOff::lease_blocking`].
+    pub fn lease_blocking(&self) -> Option<Lease<T>> {
+        self.0.lease_blocking()
+    }
+
+    /// Reserves the value, to be committed or aborted later. See
+    /// [`HandOff::reserve`].
+    pub fn reserve(&self) -> Option<Reservation<T>> {
+        self.0.reserve()
+    }
+
+    /// Runs `f` on a reference to the value without taking it. See
+    /// [`HandOff::with_ref`].
+    pub fn with_ref<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.0.with_ref(f)
+    }
+
+    /// Returns the current state of the handoff. See [`HandOff::state`].
+    pub fn state(&self) -> HandOffState {
+        self.0.state()
+    }
+
+    /// Returns `true` if the value was taken by some handle.
+    pub fn is_taken(&self) -> bool {
+        self.0.is_taken()
+    }
+
+    /// Returns the record of the take that won the value. See
+    /// [`HandOff::taken_by`].
+    #[cfg(feature = "diagnostics")]
+    pub fn taken_by(&self) -> Option<TakenBy> {
+        self.0.taken_by()
+    }
+
+    /// Returns `true` if the handoff holds a value that can be taken.
+    pub fn is_available(&self) -> bool {
+        self.0.is_available()
+    }
+}
+
+impl<T: Clone> Taker<T> {
+    /// Returns a clone of the value without taking it. See
+    /// [`HandOff::peek_cloned`].
+    pub fn peek_cloned(&self) -> Option<T> {
+        self.0.peek_cloned()
+    }
+}
+
+impl<T> Clone for Taker<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Taker<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        self.0.0.fmt_debug("Taker", fmt)
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use crate::HandOff;
+
+/// A set of handoffs whose values can be taken all at once, or not at all.
+///
+/// Implemented for slices and arrays of handoffs of the same type, and for
+/// tuples of up to six references to handoffs of different types.
+///
+/// Every `HandOff` in the set is claimed in a global order (by address) before
+/// any value is taken, so two threads taking overlapping sets never deadlock,
+/// and a thread that fails to take one value leaves all the others in place.
+/// While a set is being taken, its values count as claimed to other handles.
+///
+/// # Example
+/// ```
+/// use takeit::{ HandOff, TakeAll };
+///
+/// let connection = HandOff::new(String::from("db"));
+/// let quota = HandOff::new(3u32);
+///
+/// assert_eq!((&connection, &quota).take_all(), Some((String::from("db"), 3)));
+/// assert_eq!((&connection, &quota).take_all(), None);
+/// ```
+pub trait TakeAll {
+    /// The values of the set.
+    type Output;
+
+    /// Takes the value of every `HandOff` in the set, or none of them.
+    ///
+    /// # Errors
+    /// If any `HandOff` holds no value that can be taken right now, or the
+    /// same `HandOff` appears more than once, no value is taken and it returns
+    /// `None`.
+    fn take_all(self) -> Option<Self::Output>;
+}
+
+/// Returns the address of the state shared by the clones of `handoff`, which
+/// defines the order in which handoffs are locked.
+fn lock_order<T>(handoff: &HandOff<T>) -> usize {
+    Arc::as_ptr(&handoff.0) as *const () as usize
+}
+
+/// Sorts the indices of `addresses` in lock order, or returns `None` if an
+/// address appears twice.
+fn sorted_indices(addresses: &[usize]) -> Option<Vec<usize>> {
+    let mut indices: Vec<usize> = (0..addresses.len()).collect();
+    indices.sort_unstable_by_key(|&index| addresses[index]);
+
+    let unique = indices
+        .windows(2)
+        .all(|pair| addresses[pair[0]] != addresses[pair[1]]);
+    unique.then_some(indices)
+}
+
+impl<T> TakeAll for &[HandOff<T>] {
+    type Output = Vec<T>;
+
+    fn take_all(self) -> Option<Vec<T>> {
+        let addresses: Vec<_> = self.iter().map(lock_order).collect();
+        let mut locked_values: Vec<_> = self.iter().map(|_| None).collect();
+
+        // Dropping the guards on an early return puts the values back.
+        for index in sorted_indices(&addresses)? {
+            locked_values[index] = Some(self[index].0.lock().ok()?);
+        }
+
+        Some(locked_values.into_iter().flatten().map(|val| val.take()).collect())
+    }
+}
+
+impl<T, const N: usize> TakeAll for &[HandOff<T>; N] {
+    type Output = [T; N];
+
+    fn take_all(self) -> Option<[T; N]> {
+        let values = self.as_slice().take_all()?;
+        values.try_into().ok()
+    }
+}
+
+macro_rules! impl_take_all_tuple {
+    ($($index:tt $name:ident),+) => {
+        impl<'a, $($name),+> TakeAll for ($(&'a HandOff<$name>,)+) {
+            type Output = ($($name,)+);
+
+            #[allow(non_snake_case)]
+            fn take_all(self) -> Option<Self::Output> {
+                let addresses = [$(lock_order(self.$index)),+];
+                $(let mut $name = None;)+
+
+                // Dropping the guards on an early return puts the values back.
+                for index in sorted_indices(&addresses)? {
+                    match index {
+                        $($index => $name = Some(self.$index.0.lock().ok()?),)+
+                        _ => unreachable!(),
+                    }
+                }
+
+                Some(($($name?.take(),)+))
+            }
+        }
+    };
+}
+
+impl_take_all_tuple!(0 A);
+impl_take_all_tuple!(0 A, 1 B);
+impl_take_all_tuple!(0 A, 1 B, 2 C);
+impl_take_all_tuple!(0 A, 1 B, 2 C, 3 D);
+impl_take_all_tuple!(0 A, 1 B, 2 C, 3 D, 4 E);
+impl_take_all_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+
+impl<T> HandOff<T> {
+    /// Takes the value of every `HandOff` in `handoffs`, or none of them.
+    /// See [`TakeAll`] for sets of handoffs of different types.
+    ///
+    /// Every `HandOff` is claimed in a global order before any value is taken,
+    /// so two threads taking overlapping sets never deadlock, and a failed
+    /// attempt leaves all the values in place.
+    ///
+    /// # Errors
+    /// If any `HandOff` holds no value that can be taken right now, or the
+    /// same `HandOff` appears more than once, no value is taken and it returns
+    /// `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use takeit::HandOff;
+    ///
+    /// let forks = [HandOff::new(1), HandOff::new(2), HandOff::new(3)];
+    /// let left_right = [forks[0].clone(), forks[1].clone()];
+    /// let right_other = [forks[1].clone(), forks[2].clone()];
+    ///
+    /// assert_eq!(HandOff::take_all(&left_right), Some(vec![1, 2]));
+    /// assert_eq!(HandOff::take_all(&right_other), None);
+    /// assert!(forks[2].is_available());
+    /// ```
+    pub fn take_all(handoffs: &[HandOff<T>]) -> Option<Vec<T>> {
+        handoffs.take_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_take_all_overlapping_threads() {
+        let resources: Vec<_> = (0..4).map(HandOff::new).collect();
+
+        let threads: Vec<_> = (0..4)
+            .map(|id| {
+                let wanted = [resources[id].clone(), resources[(id + 1) % 4].clone()];
+                thread::spawn(move || HandOff::take_all(&wanted))
+            })
+            .collect();
+
+        let taken: Vec<_> = threads
+            .into_iter()
+            .filter_map(|thread| thread.join().unwrap())
+            .flatten()
+            .collect();
+        let left: Vec<_> = resources.iter().filter_map(|resource| resource.try_take().ok()).collect();
+
+        let mut all: Vec<_> = taken.into_iter().chain(left).collect();
+        all.sort_unstable();
+        assert_eq!(all, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_take_all_duplicate() {
+        let handoff = HandOff::new(1);
+        let other = HandOff::new("other");
+
+        assert_eq!((&handoff, &other, &handoff.clone()).take_all(), None);
+        assert_eq!([handoff.clone(), handoff.clone()].take_all(), None);
+        assert!(handoff.is_available());
+        assert!(other.is_available());
+
+        assert_eq!((&other, &handoff).take_all(), Some(("other", 1)));
+    }
+}
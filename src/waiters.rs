@@ -0,0 +1,192 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{ fence, AtomicUsize, Ordering };
+use std::sync::{ Arc, Mutex, MutexGuard, PoisonError };
+use std::task::{ Context, Poll, Wake, Waker };
+use std::thread::{ self, Thread };
+use std::time::Instant;
+
+/// The wakers of every handle waiting for a `HandOff` to change its state.
+///
+/// Notifying is free while nobody waits, so the take fast path never touches
+/// the mutex.
+pub(crate) struct Waiters {
+    count: AtomicUsize,
+    list: Mutex<WaiterList>,
+}
+
+#[derive(Default)]
+struct WaiterList {
+    next_key: usize,
+    wakers: Vec<(usize, Waker)>,
+}
+
+impl Waiters {
+    pub(crate) fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            list: Mutex::new(WaiterList::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WaiterList> {
+        // No user code runs while the list is locked, so a poisoned list is
+        // still consistent.
+        self.list.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Registers `waker` to be woken on the next notification.
+    ///
+    /// `key` identifies the registration: it is filled on the first call and
+    /// reused to replace the waker on later calls. The caller must check the
+    /// state it is waiting for again after registering.
+    pub(crate) fn register(&self, key: &mut Option<usize>, waker: &Waker) {
+        {
+            let mut list = self.lock();
+            let existing = key.and_then(|key| {
+                list.wakers.iter_mut().find(|(other, _)| *other == key)
+            });
+
+            match existing {
+                Some((_, old)) => old.clone_from(waker),
+                None => {
+                    let new_key = list.next_key;
+                    list.next_key = list.next_key.wrapping_add(1);
+                    list.wakers.push((new_key, waker.clone()));
+                    self.count.fetch_add(1, Ordering::Relaxed);
+                    *key = Some(new_key);
+                },
+            }
+        }
+
+        // Pairs with the fence in `notify`: either the notifier sees our
+        // registration, or we see the state it published.
+        fence(Ordering::SeqCst);
+    }
+
+    /// Removes a registration that was not woken yet.
+    pub(crate) fn unregister(&self, key: &mut Option<usize>) {
+        let Some(key) = key.take() else {
+            return;
+        };
+
+        let mut list = self.lock();
+        if let Some(index) = list.wakers.iter().position(|(other, _)| *other == key) {
+            list.wakers.swap_remove(index);
+            self.count.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Wakes every registered waiter. Must be called after publishing the new
+    /// state.
+    pub(crate) fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        let wakers = {
+            let mut list = self.lock();
+            self.count.store(0, Ordering::Relaxed);
+            std::mem::take(&mut list.wakers)
+        };
+
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Polls `poll`, registering the task of `cx` to be woken on the next
+    /// notification if it is not ready yet.
+    pub(crate) fn poll<R>(
+        &self,
+        key: &mut Option<usize>,
+        cx: &mut Context<'_>,
+        mut poll: impl FnMut() -> Option<R>,
+    ) -> Poll<R> {
+        if let Some(ready) = poll() {
+            self.unregister(key);
+            return Poll::Ready(ready);
+        }
+
+        self.register(key, cx.waker());
+        match poll() {
+            Some(ready) => {
+                self.unregister(key);
+                Poll::Ready(ready)
+            },
+            None => Poll::Pending,
+        }
+    }
+
+    /// Blocks the current thread until `poll` returns `Some`, re-running it
+    /// after every notification.
+    ///
+    /// Returns `None` if `deadline` passes first.
+    pub(crate) fn block_on<R>(
+        &self,
+        deadline: Option<Instant>,
+        mut poll: impl FnMut() -> Option<R>,
+    ) -> Option<R> {
+        if let Some(ready) = poll() {
+            return Some(ready);
+        }
+
+        let waker = thread_waker();
+        let mut key = None;
+
+        let ready = loop {
+            self.register(&mut key, &waker);
+            if let Some(ready) = poll() {
+                break Some(ready);
+            }
+
+            match deadline {
+                None => thread::park(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break None;
+                    }
+                    thread::park_timeout(deadline - now);
+                },
+            }
+        };
+
+        self.unregister(&mut key);
+        ready
+    }
+}
+
+/// Runs `future` to completion, parking the current thread while it is
+/// pending.
+pub(crate) fn block_on_future<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = thread_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Returns a waker that unparks the current thread.
+pub(crate) fn thread_waker() -> Waker {
+    Waker::from(Arc::new(ThreadWaker(thread::current())))
+}
+
+/// Wakes a thread blocked in `Waiters::block_on` or `block_on_future`.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}